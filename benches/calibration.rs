@@ -0,0 +1,31 @@
+#![feature(test)]
+
+extern crate test;
+
+use advent_of_code::calibration::{calibration_total, calibration_total_naive, Lexicon};
+use test::Bencher;
+
+// Long lines with their first and last tokens well inside, so both ends are
+// scanned a long way in.
+fn long_lines() -> Vec<String> {
+    (0..100)
+        .map(|i| {
+            let noise = "xyzzy thre ei nin sevem ".repeat(200);
+            format!("{}eightwo{}{}oneight{}", noise, i % 10, noise, noise)
+        })
+        .collect()
+}
+
+#[bench]
+fn matcher_long_lines(b: &mut Bencher) {
+    let lines = long_lines();
+    let lexicon = Lexicon::english();
+    b.iter(|| calibration_total(&lines, &lexicon));
+}
+
+#[bench]
+fn naive_long_lines(b: &mut Bencher) {
+    let lines = long_lines();
+    let lexicon = Lexicon::english();
+    b.iter(|| calibration_total_naive(&lines, &lexicon));
+}
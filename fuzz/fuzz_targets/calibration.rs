@@ -0,0 +1,22 @@
+#![no_main]
+
+use advent_of_code::{calibration, io};
+use libfuzzer_sys::fuzz_target;
+
+// The matcher has to agree with trying every token at every position.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let input = io::normalize(text);
+        let lines = io::lines(&input);
+        for lexicon in [
+            calibration::Lexicon::english(),
+            calibration::Lexicon::german(),
+            calibration::Lexicon::english().with("twone", 5).with("o", 3),
+        ] {
+            assert_eq!(
+                calibration::calibration_total(&lines, &lexicon),
+                calibration::calibration_total_naive(&lines, &lexicon)
+            );
+        }
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use advent_of_code::{hash, io};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let input = io::normalize(text);
+        let _ = hash::Commands::from_line(&input);
+        let _ = hash::Boxes::from_line(&input);
+    }
+});
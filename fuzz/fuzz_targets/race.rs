@@ -0,0 +1,12 @@
+#![no_main]
+
+use advent_of_code::{io, race};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let input = io::normalize(text);
+        let _ = race::Races::from_lines(&io::lines(&input));
+        let _ = race::Race::from_lines(&io::lines(&input)).map(|race| race.num_success());
+    }
+});
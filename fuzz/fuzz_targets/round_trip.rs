@@ -0,0 +1,17 @@
+#![no_main]
+
+use advent_of_code::writer;
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the day, the rest is its input. Whatever parses has to
+// write out as text that parses back to the same model.
+fuzz_target!(|data: &[u8]| {
+    if let Some((day, rest)) = data.split_first() {
+        if let Ok(text) = std::str::from_utf8(rest) {
+            let day = day % 25 + 1;
+            if let Ok(round_trips) = writer::round_trips(day, text) {
+                assert!(round_trips, "day {} does not round trip", day);
+            }
+        }
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use std::time::Duration;
+
+use advent_of_code::control::Control;
+use advent_of_code::{challenge, io};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the challenge, the rest is its input. Every
+// implementation has to answer or report an error instead of panicking, and
+// those that tick give up at the timeout instead of stalling the fuzzer.
+fuzz_target!(|data: &[u8]| {
+    if let Some((num, rest)) = data.split_first() {
+        if let Ok(text) = std::str::from_utf8(rest) {
+            let num = num % 50 + 1;
+            let input = io::normalize(text);
+            for name in challenge::implementations(num) {
+                let control = Control::new().with_timeout(Duration::from_millis(100));
+                let _ = challenge::challenge_impl(num, name, &input, &control);
+            }
+        }
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use advent_of_code::{io, steps};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let input = io::normalize(text);
+        let _ = steps::Map::from_grid(&io::grid(&input));
+    }
+});
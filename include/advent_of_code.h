@@ -0,0 +1,30 @@
+#ifndef ADVENT_OF_CODE_H
+#define ADVENT_OF_CODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Link against the shared library built with
+ * cargo rustc --lib --release --features ffi --crate-type cdylib */
+
+#define SOLVE_OK 0
+#define SOLVE_PARSE 1
+#define SOLVE_OVERFLOW 2
+#define SOLVE_NO_SOLVER 3
+#define SOLVE_TIMED_OUT 4
+#define SOLVE_CANCELLED 5
+#define SOLVE_PANIC 6
+#define SOLVE_INVALID_INPUT 7
+#define SOLVE_ASSUMPTION 8
+#define SOLVE_NO_ANSWER 9
+
+/* On success text holds the answer, otherwise a description of the error. */
+typedef struct {
+    int32_t status;
+    char *text;
+} SolveResult;
+
+SolveResult aoc_solve(uint8_t day, uint8_t part, const uint8_t *input_ptr, size_t len);
+void aoc_solve_free(SolveResult result);
+
+#endif
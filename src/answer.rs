@@ -0,0 +1,111 @@
+use num::{CheckedAdd, CheckedMul, One, Zero};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    U32(u32),
+    U64(u64),
+    I64(i64),
+    Text(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    Parse,
+    Overflow,
+    NoSolver,
+    TimedOut,
+    Cancelled,
+    Panic,
+    Assumption,
+    // The input is valid but has no answer, such as a goal nothing reaches.
+    NoAnswer,
+}
+
+impl From<u32> for Answer {
+    fn from(val: u32) -> Answer {
+        Answer::U32(val)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(val: u64) -> Answer {
+        Answer::U64(val)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(val: usize) -> Answer {
+        Answer::U64(val as u64)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(val: i64) -> Answer {
+        Answer::I64(val)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(val: String) -> Answer {
+        Answer::Text(val)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::U32(val) => write!(f, "{}", val),
+            Answer::U64(val) => write!(f, "{}", val),
+            Answer::I64(val) => write!(f, "{}", val),
+            Answer::Text(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse => write!(f, "failed to parse input"),
+            SolveError::Overflow => write!(f, "answer overflowed"),
+            SolveError::NoSolver => write!(f, "no solver for this challenge"),
+            SolveError::TimedOut => write!(f, "timed out"),
+            SolveError::Cancelled => write!(f, "cancelled"),
+            SolveError::Panic => write!(f, "solver panicked"),
+            SolveError::Assumption => write!(f, "input breaks an assumption of the solver"),
+            SolveError::NoAnswer => write!(f, "input has no answer"),
+        }
+    }
+}
+
+// Solvers return None from the checked helpers on overflow.
+pub fn checked<T: Into<Answer>>(val: Option<T>) -> Result<Answer, SolveError> {
+    val.map(|v| v.into()).ok_or(SolveError::Overflow)
+}
+
+pub fn checked_sum<T, I>(vals: I) -> Option<T>
+where
+    T: CheckedAdd + Zero,
+    I: IntoIterator<Item = T>,
+{
+    vals.into_iter()
+        .try_fold(T::zero(), |total, val| total.checked_add(&val))
+}
+
+pub fn checked_product<T, I>(vals: I) -> Option<T>
+where
+    T: CheckedMul + One,
+    I: IntoIterator<Item = T>,
+{
+    vals.into_iter()
+        .try_fold(T::one(), |total, val| total.checked_mul(&val))
+}
+
+pub fn checked_sum_opt<T, I>(vals: I) -> Option<T>
+where
+    T: CheckedAdd + Zero,
+    I: IntoIterator<Item = Option<T>>,
+{
+    vals.into_iter()
+        .try_fold(T::zero(), |total, val| total.checked_add(&val?))
+}
@@ -0,0 +1,24 @@
+use crate::answer::SolveError;
+
+// A property of the input that a solver's shortcut relies on, and whether a
+// given input has it.
+pub struct Assumption {
+    pub description: &'static str,
+    pub holds: bool,
+}
+
+impl Assumption {
+    pub fn new(description: &'static str, holds: bool) -> Assumption {
+        Assumption { description, holds }
+    }
+}
+
+// Solvers call this before taking a shortcut, so an input breaking it gets an
+// error instead of a wrong answer.
+pub fn require(assumptions: &[Assumption]) -> Result<(), SolveError> {
+    if assumptions.iter().all(|assumption| assumption.holds) {
+        Ok(())
+    } else {
+        Err(SolveError::Assumption)
+    }
+}
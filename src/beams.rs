@@ -1,5 +1,10 @@
+use crate::simulation::Simulation;
+use crate::utils::render;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Beam {
     fixed: HashSet<(i64, i64)>,
     movable: HashSet<(i64, i64)>,
@@ -134,6 +139,29 @@ impl Beam {
         self.fixed.contains(&(i, j)) || self.movable.contains(&(i, j))
     }
 
+    pub fn render(&self) -> String {
+        render(self.height, self.width, |i, j| {
+            if self.fixed.contains(&(i, j)) {
+                '#'
+            } else if self.movable.contains(&(i, j)) {
+                'O'
+            } else {
+                '.'
+            }
+        })
+    }
+
+    // The platform before and after each tilt of the first spin cycle.
+    pub fn spin_frames(&self) -> Vec<String> {
+        let mut beam = self.clone();
+        let mut frames = vec![beam.render()];
+        for tilt in [Beam::north, Beam::west, Beam::south, Beam::east] {
+            tilt(&mut beam);
+            frames.push(beam.render());
+        }
+        frames
+    }
+
     fn score(&self) -> usize {
         self.movable
             .iter()
@@ -141,3 +169,33 @@ impl Beam {
             .sum()
     }
 }
+
+// A tick is one spin cycle, and the platform is finished once a cycle leaves
+// every rock where it was.
+impl Simulation for Beam {
+    type Snapshot = HashSet<(i64, i64)>;
+
+    fn step(&mut self) -> bool {
+        let before = self.movable.clone();
+        self.spin();
+        self.movable != before
+    }
+
+    fn snapshot(&self) -> HashSet<(i64, i64)> {
+        self.movable.clone()
+    }
+
+    fn restore(&mut self, snapshot: &HashSet<(i64, i64)>) {
+        self.movable = snapshot.clone();
+    }
+
+    fn state(&self) -> String {
+        self.render()
+    }
+}
+
+impl fmt::Display for Beam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
@@ -1,10 +1,20 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::{BTreeSet, HashSet};
+use std::fmt;
 
+use crate::simulation::Simulation;
+use crate::utils::join;
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Brick {
     start: (i64, i64, i64),
     end: (i64, i64, i64),
 }
 
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bricks {
     bricks: Vec<Brick>,
 }
@@ -18,8 +28,8 @@ fn point(string: &str) -> Option<(i64, i64, i64)> {
 }
 
 fn chain_count(
-    dependencies: &Vec<Vec<usize>>,
-    reverse_dependencies: &Vec<Vec<usize>>,
+    dependencies: &[Vec<usize>],
+    reverse_dependencies: &[Vec<usize>],
     start: usize,
 ) -> usize {
     let mut count = 0;
@@ -43,7 +53,7 @@ fn chain_count(
 }
 
 impl Bricks {
-    pub fn from_lines(lines: &Vec<String>) -> Option<Bricks> {
+    pub fn from_lines(lines: &[String]) -> Option<Bricks> {
         let bricks = lines
             .iter()
             .map(|line| Brick::from_line(line))
@@ -66,7 +76,11 @@ impl Bricks {
     pub fn maximum_chain(&mut self) -> usize {
         self.drop_down();
         let (dependencies, reverse_dependencies) = self.depended_maps();
-        (0..self.bricks.len())
+        #[cfg(feature = "parallel")]
+        let bricks = (0..self.bricks.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let bricks = 0..self.bricks.len();
+        bricks
             .map(|i| chain_count(&dependencies, &reverse_dependencies, i))
             .sum()
     }
@@ -87,20 +101,22 @@ impl Bricks {
     }
 
     fn drop_down(&mut self) {
-        let mut moved = true;
-        while moved {
-            moved = false;
-            let mut to_move = Vec::new();
-            for (i, brick) in self.bricks.iter().enumerate() {
-                if self.holding_count(brick) == 0 && brick.start.2 > 0 {
-                    to_move.push(i);
-                    moved = true;
-                }
-            }
-            for i in to_move {
-                self.bricks[i].move_down();
-            }
+        while self.fall() {}
+    }
+
+    // Moves every unsupported brick down one, false if none could move.
+    fn fall(&mut self) -> bool {
+        let to_move: Vec<usize> = self
+            .bricks
+            .iter()
+            .enumerate()
+            .filter(|(_, brick)| self.holding_count(brick) == 0 && brick.start.2 > 0)
+            .map(|(i, _)| i)
+            .collect();
+        for i in to_move.iter() {
+            self.bricks[*i].move_down();
         }
+        !to_move.is_empty()
     }
 
     fn holding_count(&self, brick: &Brick) -> usize {
@@ -117,11 +133,36 @@ impl Bricks {
     }
 }
 
+// A tick drops every unsupported brick by one, until they have all settled.
+impl Simulation for Bricks {
+    type Snapshot = Bricks;
+
+    fn step(&mut self) -> bool {
+        self.fall()
+    }
+
+    fn snapshot(&self) -> Bricks {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: &Bricks) {
+        *self = snapshot.clone();
+    }
+
+    fn state(&self) -> String {
+        self.to_string()
+    }
+}
+
 impl Brick {
     pub fn from_line(line: &str) -> Option<Brick> {
         let (start_str, end_str) = line.split_once("~")?;
         let start = point(start_str)?;
         let end = point(end_str)?;
+        // Bricks rest on the ground at z = 0, so everything has to sit above it.
+        if start.2 < 1 || end.2 < start.2 {
+            return None;
+        }
         Some(Brick { start, end })
     }
 
@@ -153,3 +194,16 @@ impl Brick {
         other.start.2 <= self.end.2 && other.end.2 >= self.start.2
     }
 }
+
+impl fmt::Display for Brick {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (s, e) = (self.start, self.end);
+        write!(f, "{},{},{}~{},{},{}", s.0, s.1, s.2, e.0, e.1, e.2)
+    }
+}
+
+impl fmt::Display for Bricks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.bricks, "\n")
+    }
+}
@@ -1,3 +1,7 @@
+use crate::answer::checked_sum;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
 pub fn is_num(ch: char) -> Option<u8> {
     match ch {
         '0' => Some(0),
@@ -13,106 +17,511 @@ pub fn is_num(ch: char) -> Option<u8> {
         _ => None,
     }
 }
-fn prefix(string: &str) -> Option<u8> {
-    if let Some(ch) = string.chars().next() {
-        if let Some(val) = is_num(ch) {
-            return Some(val);
+// The words that count as digits alongside the digit characters themselves,
+// each with the value it stands for.
+#[derive(Clone)]
+pub struct Lexicon {
+    words: Vec<(String, u8)>,
+    matcher: Matcher,
+}
+
+// Marks a transition not yet in the automaton while it is being built.
+const NO_STATE: u32 = u32::MAX;
+
+// An Aho-Corasick automaton over the digit characters and a lexicon's words.
+// Every state has a transition on every byte, so a line is scanned once
+// from the front without backtracking, and every token ending at a byte is
+// found, overlapping ones included.
+#[derive(Clone)]
+struct Matcher {
+    next: Vec<[u32; 256]>,
+    // The longest token ending in each state, as its length and value.
+    token: Vec<Option<(usize, u8)>>,
+    // The nearest state along the failure links with a token of its own.
+    dict: Vec<Option<u32>>,
+}
+
+// A token matched in a line, by byte position.
+#[derive(Clone, Copy)]
+struct Token {
+    start: usize,
+    len: usize,
+    value: u8,
+}
+
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+const ENGLISH: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const GERMAN: [&str; 9] = [
+    "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const FRENCH: [&str; 9] = [
+    "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+
+impl Lexicon {
+    // No words, only the digit characters count.
+    pub fn digits() -> Lexicon {
+        Lexicon {
+            words: Vec::new(),
+            matcher: Matcher::new(&[]),
+        }
+    }
+
+    fn numbered(words: &[&str]) -> Lexicon {
+        words
+            .iter()
+            .zip(1..)
+            .fold(Lexicon::digits(), |lexicon, (word, val)| {
+                lexicon.with(word, val)
+            })
+    }
+
+    // One to nine, as in the puzzle.
+    pub fn english() -> Lexicon {
+        Lexicon::numbered(&ENGLISH)
+    }
+
+    pub fn german() -> Lexicon {
+        Lexicon::numbered(&GERMAN)
+    }
+
+    pub fn french() -> Lexicon {
+        Lexicon::numbered(&FRENCH)
+    }
+
+    pub fn by_name(name: &str) -> Option<Lexicon> {
+        match name {
+            "digits" => Some(Lexicon::digits()),
+            "english" => Some(Lexicon::english()),
+            "german" => Some(Lexicon::german()),
+            "french" => Some(Lexicon::french()),
+            _ => None,
+        }
+    }
+
+    // Adds a word standing for a digit, such as a custom token.
+    pub fn with(mut self, word: &str, val: u8) -> Lexicon {
+        self.words.push((word.to_string(), val));
+        self.matcher = Matcher::new(&self.words);
+        self
+    }
+
+    // A word and its digit per line, such as "eins 1". Blank lines and lines
+    // starting with # are skipped.
+    pub fn from_string(string: &str) -> Option<Lexicon> {
+        let mut lexicon = Lexicon::digits();
+        for line in string.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, val_str) = line.split_once(char::is_whitespace)?;
+            let val = val_str.trim().parse::<u8>().ok().filter(|val| *val < 10)?;
+            lexicon = lexicon.with(word, val);
+        }
+        Some(lexicon)
+    }
+
+    // Every token, the digit characters first so a word added later can
+    // take over one of them.
+    fn tokens(&self) -> impl Iterator<Item = (&str, u8)> {
+        let digits = DIGITS.iter().zip(0..).map(|(digit, val)| (*digit, val));
+        let words = self.words.iter().map(|(word, val)| (word.as_str(), *val));
+        digits.chain(words.filter(|(word, _)| !word.is_empty()))
+    }
+
+    // Where more than one token matches, the longest wins, and of those the
+    // last added.
+    fn prefix(&self, string: &str) -> Option<u8> {
+        self.tokens()
+            .filter(|(word, _)| string.starts_with(word))
+            .max_by_key(|(word, _)| word.len())
+            .map(|(_, val)| val)
+    }
+
+    fn suffix(&self, string: &str) -> Option<u8> {
+        self.tokens()
+            .filter(|(word, _)| string.ends_with(word))
+            .max_by_key(|(word, _)| word.len())
+            .map(|(_, val)| val)
+    }
+}
+
+impl Token {
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    // Of two tokens starting together, the longer counts as first.
+    fn starts_before(&self, other: &Token) -> bool {
+        (self.start, other.len) < (other.start, self.len)
+    }
+
+    // Of two tokens ending together, the longer counts as last.
+    fn ends_after(&self, other: &Token) -> bool {
+        (self.end(), self.len) > (other.end(), other.len)
+    }
+}
+
+impl Matcher {
+    fn new(words: &[(String, u8)]) -> Matcher {
+        let mut matcher = Matcher {
+            next: vec![[NO_STATE; 256]],
+            token: vec![None],
+            dict: vec![None],
+        };
+        let digits = DIGITS.iter().zip(0..).map(|(digit, val)| (*digit, val));
+        let words = words.iter().map(|(word, val)| (word.as_str(), *val));
+        for (word, val) in digits.chain(words) {
+            matcher.insert(word.as_bytes(), val);
+        }
+        matcher.link();
+        matcher
+    }
+
+    fn insert(&mut self, word: &[u8], val: u8) {
+        if word.is_empty() {
+            return;
+        }
+        let mut state = 0;
+        for &byte in word {
+            if self.next[state][byte as usize] == NO_STATE {
+                self.next[state][byte as usize] = self.next.len() as u32;
+                self.next.push([NO_STATE; 256]);
+                self.token.push(None);
+                self.dict.push(None);
+            }
+            state = self.next[state][byte as usize] as usize;
         }
+        self.token[state] = Some((word.len(), val));
     }
-    let len = string.len();
-    if len >= 4 && string[..4] == *"zero" {
-        Some(0)
-    } else if len >= 3 && string[..3] == *"one" {
-        Some(1)
-    } else if len >= 3 && string[..3] == *"two" {
-        Some(2)
-    } else if len >= 5 && string[..5] == *"three" {
-        Some(3)
-    } else if len >= 4 && string[..4] == *"four" {
-        Some(4)
-    } else if len >= 4 && string[..4] == *"five" {
-        Some(5)
-    } else if len >= 3 && string[..3] == *"six" {
-        Some(6)
-    } else if len >= 5 && string[..5] == *"seven" {
-        Some(7)
-    } else if len >= 5 && string[..5] == *"eight" {
-        Some(8)
-    } else if len >= 4 && string[..4] == *"nine" {
-        Some(9)
-    } else {
-        None
-    }
-}
-
-fn end(string: &str, n: usize) -> &str {
-    let i = string.len() - n;
-    &string[i..]
-}
-
-fn suffix(string: &str) -> Option<u8> {
-    if let Some(ch) = string.chars().rev().next() {
-        if let Some(val) = is_num(ch) {
-            return Some(val);
+
+    // Fills in the missing transitions from the failure links, breadth first
+    // so a state's failure is always complete before its children need it.
+    fn link(&mut self) {
+        let mut fail = vec![0_usize; self.next.len()];
+        let mut queue = VecDeque::new();
+        for byte in 0..256 {
+            match self.next[0][byte] {
+                NO_STATE => self.next[0][byte] = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let fallback = fail[state];
+            self.dict[state] = match self.token[fallback] {
+                Some(_) => Some(fallback as u32),
+                None => self.dict[fallback],
+            };
+            for byte in 0..256 {
+                match self.next[state][byte] {
+                    NO_STATE => self.next[state][byte] = self.next[fallback][byte],
+                    child => {
+                        fail[child as usize] = self.next[fallback][byte] as usize;
+                        queue.push_back(child as usize);
+                    }
+                }
+            }
+        }
+    }
+
+    // The first and last tokens in text: the one starting earliest and the
+    // one ending latest, the longer winning a tie. Tokens may overlap, so
+    // "eightwo" starts with eight and ends with two.
+    fn first_last(&self, text: &str) -> Option<(Token, Token)> {
+        let mut state = 0;
+        let mut found: Option<(Token, Token)> = None;
+        for (i, &byte) in text.as_bytes().iter().enumerate() {
+            state = self.next[state][byte as usize] as usize;
+            let mut at = match self.token[state] {
+                Some(_) => Some(state),
+                None => self.dict[state].map(|s| s as usize),
+            };
+            while let Some(s) = at {
+                if let Some((len, value)) = self.token[s] {
+                    let token = Token {
+                        start: i + 1 - len,
+                        len,
+                        value,
+                    };
+                    found = Some(match found {
+                        None => (token, token),
+                        Some((first, last)) => (
+                            if token.starts_before(&first) {
+                                token
+                            } else {
+                                first
+                            },
+                            if token.ends_after(&last) { token } else { last },
+                        ),
+                    });
+                }
+                at = self.dict[s].map(|s| s as usize);
+            }
         }
+        found
     }
-    let len = string.len();
-    if len >= 4 && *end(string, 4) == *"zero" {
-        Some(0)
-    } else if len >= 3 && *end(string, 3) == *"one" {
-        Some(1)
-    } else if len >= 3 && *end(string, 3) == *"two" {
-        Some(2)
-    } else if len >= 5 && *end(string, 5) == *"three" {
-        Some(3)
-    } else if len >= 4 && *end(string, 4) == *"four" {
-        Some(4)
-    } else if len >= 4 && *end(string, 4) == *"five" {
-        Some(5)
-    } else if len >= 3 && *end(string, 3) == *"six" {
-        Some(6)
-    } else if len >= 5 && *end(string, 5) == *"seven" {
-        Some(7)
-    } else if len >= 5 && *end(string, 5) == *"eight" {
-        Some(8)
-    } else if len >= 4 && *end(string, 4) == *"nine" {
-        Some(9)
-    } else {
-        None
-    }
-}
-
-fn right_value_digits(text: &str) -> u8 {
+}
+
+fn right_value_digits(text: &str, lexicon: &Lexicon) -> u8 {
     text.char_indices()
-        .filter_map(|(pos, _)| prefix(&text[pos..]))
+        .filter_map(|(pos, _)| lexicon.prefix(&text[pos..]))
         .next()
         .unwrap_or(0)
 }
 
-fn left_value_digits(text: &str) -> u8 {
+fn left_value_digits(text: &str, lexicon: &Lexicon) -> u8 {
     text.char_indices()
         .rev()
-        .filter_map(|(pos, _)| suffix(&text[..=pos]))
+        .filter_map(|(pos, ch)| lexicon.suffix(&text[..pos + ch.len_utf8()]))
         .next()
         .unwrap_or(0)
 }
 
-fn right_value(text: &str) -> u8 {
-    text.chars().filter_map(is_num).next().unwrap_or(0)
+fn value(text: &str, lexicon: &Lexicon) -> u64 {
+    match lexicon.matcher.first_last(text) {
+        Some((first, last)) => first.value as u64 * 10 + last.value as u64,
+        None => 0,
+    }
+}
+
+// Tries every token at every position of the line, as the matcher replaced
+// here did. Kept as a reference to check the matcher against and to
+// benchmark it with.
+fn value_naive(text: &str, lexicon: &Lexicon) -> u64 {
+    right_value_digits(text, lexicon) as u64 * 10 + left_value_digits(text, lexicon) as u64
+}
+
+pub fn calibration_total_naive(lines: &[String], lexicon: &Lexicon) -> Option<u64> {
+    checked_sum(lines.iter().map(|line| value_naive(line, lexicon)))
 }
 
-fn left_value(text: &str) -> u8 {
-    text.chars().rev().filter_map(is_num).next().unwrap_or(0)
+pub fn calibration_total(lines: &[String], lexicon: &Lexicon) -> Option<u64> {
+    checked_sum(lines.iter().map(|line| value(line, lexicon)))
 }
 
-fn value(text: &str, digits: bool) -> u8 {
-    if digits {
-        right_value_digits(text) * 10 + left_value_digits(text)
-    } else {
-        right_value(text) * 10 + left_value(text)
+// The lines and total of one run of lines in a stream, numbered from 1.
+pub struct Subtotal {
+    pub first_line: usize,
+    pub lines: usize,
+    pub total: u64,
+}
+
+// Sums a stream a line at a time, reusing one buffer so memory stays the
+// same however long the stream is. Lines that are not valid UTF-8 are read
+// lossily rather than stopping the sum. With chunk_lines, each run of that
+// many lines has its subtotal passed to on_chunk as it completes, the last
+// run possibly shorter. Ok(None) if the total overflows.
+pub fn calibration_stream(
+    mut reader: impl BufRead,
+    lexicon: &Lexicon,
+    chunk_lines: Option<usize>,
+    mut on_chunk: impl FnMut(Subtotal),
+) -> io::Result<Option<u64>> {
+    let mut buf: Vec<u8> = Vec::new();
+    let mut total: u64 = 0;
+    let mut chunk = Subtotal {
+        first_line: 1,
+        lines: 0,
+        total: 0,
+    };
+    let mut line_num = 0;
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        line_num += 1;
+        let line = String::from_utf8_lossy(&buf);
+        let val = value(line.trim_end_matches(['\n', '\r']), lexicon);
+        let Some(sum) = total.checked_add(val) else {
+            return Ok(None);
+        };
+        total = sum;
+        // A chunk's subtotal is never more than the total.
+        chunk.total += val;
+        chunk.lines += 1;
+        if Some(chunk.lines) == chunk_lines {
+            let next = Subtotal {
+                first_line: line_num + 1,
+                lines: 0,
+                total: 0,
+            };
+            on_chunk(std::mem::replace(&mut chunk, next));
+        }
+    }
+    if chunk_lines.is_some() && chunk.lines > 0 {
+        on_chunk(chunk);
     }
+    Ok(Some(total))
+}
+
+// A token picked out of a line, by byte position, and whether it was a
+// spelled out word rather than a digit character.
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub value: u8,
+    pub word: bool,
+}
+
+// How one line came to its value. Lines with no token count as 0.
+pub struct LineReport {
+    pub line: usize,
+    pub first: Option<Match>,
+    pub last: Option<Match>,
+    pub value: u64,
+    // What the line is worth counting only the digit characters.
+    pub digit_value: u64,
 }
 
-pub fn calibration_total(lines: &Vec<String>, digits: bool) -> u32 {
-    lines.iter().map(|line| value(line, digits) as u32).sum()
+impl LineReport {
+    pub fn no_match(&self) -> bool {
+        self.first.is_none()
+    }
+
+    pub fn differs(&self) -> bool {
+        self.value != self.digit_value
+    }
+}
+
+impl Token {
+    fn to_match(self, text: &str) -> Match {
+        let token = &text[self.start..self.end()];
+        Match {
+            start: self.start,
+            end: self.end(),
+            text: token.to_string(),
+            value: self.value,
+            word: !(token.len() == 1 && token.as_bytes()[0].is_ascii_digit()),
+        }
+    }
+}
+
+// The first and last token of every line, numbered from 1, with what each
+// line is worth with the lexicon and with digits alone.
+pub fn explain(lines: &[String], lexicon: &Lexicon) -> Vec<LineReport> {
+    let digits = Lexicon::digits();
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let found = lexicon.matcher.first_last(text);
+            LineReport {
+                line: i + 1,
+                first: found.map(|(first, _)| first.to_match(text)),
+                last: found.map(|(_, last)| last.to_match(text)),
+                value: value(text, lexicon),
+                digit_value: value(text, &digits),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_subtotals_a_chunk_at_a_time() {
+        let text = "1abc2\npqr3stu8vwx\r\na1b2c3d4e5f\ntreb7uchet\nx9";
+        let mut chunks = Vec::new();
+        let total = calibration_stream(text.as_bytes(), &Lexicon::digits(), Some(2), |chunk| {
+            chunks.push((chunk.first_line, chunk.lines, chunk.total))
+        });
+        assert_eq!(total.unwrap(), Some(241));
+        assert_eq!(chunks, [(1, 2, 50), (3, 2, 92), (5, 1, 99)]);
+        let mut called = false;
+        let total =
+            calibration_stream(text.as_bytes(), &Lexicon::digits(), None, |_| called = true);
+        assert_eq!(total.unwrap(), Some(241));
+        assert!(!called);
+    }
+
+    #[test]
+    fn streams_lines_that_are_not_utf8() {
+        let total = calibration_stream(&b"\xff4\n2\xfe"[..], &Lexicon::digits(), None, |_| {});
+        assert_eq!(total.unwrap(), Some(66));
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn reads_a_lexicon_a_word_per_line() {
+        let lexicon = Lexicon::from_string("# German\n\neins 1\n  zwei\t2  \n").unwrap();
+        assert_eq!(
+            calibration_total(&lines("zweiundeins\nxeinsx"), &lexicon),
+            Some(32)
+        );
+        // A word added later takes over from a digit or an earlier word.
+        let lexicon = Lexicon::from_string("one 1\none 7\n3 9").unwrap();
+        assert_eq!(calibration_total(&lines("one3"), &lexicon), Some(79));
+    }
+
+    #[test]
+    fn rejects_a_malformed_lexicon() {
+        assert!(Lexicon::from_string("eins").is_none());
+        assert!(Lexicon::from_string("eins one").is_none());
+        assert!(Lexicon::from_string("zehn 10").is_none());
+        assert!(Lexicon::from_string("eins -1").is_none());
+    }
+
+    // Tokens that share letters, or sit inside one another, must all be
+    // found just as trying every token at every position finds them.
+    #[test]
+    fn the_matcher_agrees_with_the_naive_scan() {
+        let nested = Lexicon::digits().with("ab", 1).with("b", 2).with("a", 3);
+        let lexicons = [
+            Lexicon::digits(),
+            Lexicon::english(),
+            Lexicon::german(),
+            Lexicon::french(),
+            nested,
+        ];
+        let lines = lines(
+            "twone\neightwo\noneight\nsevenine\nxtwone3four\nnineight\nzweins\n\
+            sixeptrois\nab\nxab\naab\nbba\nno digits\n",
+        );
+        for lexicon in &lexicons {
+            for line in &lines {
+                assert_eq!(value(line, lexicon), value_naive(line, lexicon), "{}", line);
+            }
+        }
+        let english = Lexicon::english();
+        assert_eq!(value("twone", &english), 21);
+        assert_eq!(value("eightwo", &english), 82);
+        assert_eq!(value("zweins", &Lexicon::german()), 21);
+    }
+
+    #[test]
+    fn explains_which_tokens_decide_a_line() {
+        let reports = explain(
+            &lines("two1nine\nabc\n7pqrstsixteen\npqr3stu8vwx"),
+            &Lexicon::english(),
+        );
+        let numbers: Vec<usize> = reports.iter().map(|report| report.line).collect();
+        assert_eq!(numbers, [1, 2, 3, 4]);
+        let first = reports[0].first.as_ref().unwrap();
+        assert_eq!((first.start, first.end, first.text.as_str()), (0, 3, "two"));
+        assert!(first.word);
+        let last = reports[0].last.as_ref().unwrap();
+        assert_eq!((last.start, last.end, last.value), (4, 8, 9));
+        assert_eq!((reports[0].value, reports[0].digit_value), (29, 11));
+        assert!(reports[0].differs() && !reports[0].no_match());
+        assert!(reports[1].no_match() && !reports[1].differs());
+        assert_eq!(reports[1].value, 0);
+        let first = reports[2].first.as_ref().unwrap();
+        assert_eq!(
+            (first.start, first.text.as_str(), first.word),
+            (0, "7", false)
+        );
+        assert_eq!(reports[2].last.as_ref().unwrap().text, "six");
+        assert!(reports[2].differs());
+        assert!(!reports[3].differs() && !reports[3].no_match());
+    }
 }
@@ -1,7 +1,11 @@
+use crate::answer::checked_sum_opt;
+use crate::utils::join;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Card {
     WildJack,
     Num(u8),
@@ -13,6 +17,7 @@ enum Card {
 }
 
 #[derive(Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum HandType {
     FiveOfKind,
     FourOfKind,
@@ -48,9 +53,21 @@ impl Card {
             _ => None,
         }
     }
+
+    fn to_char(self) -> char {
+        match self {
+            Card::A => 'A',
+            Card::K => 'K',
+            Card::Q => 'Q',
+            Card::J | Card::WildJack => 'J',
+            Card::T => 'T',
+            Card::Num(num) => (b'0' + num) as char,
+        }
+    }
 }
 
 #[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Hand {
     cards: [Card; 5],
     bid: usize,
@@ -92,13 +109,13 @@ impl Hand {
         let mut card_counts: HashMap<Card, usize> = HashMap::new();
         for card in &self.cards {
             card_counts
-                .entry(card.clone())
+                .entry(*card)
                 .and_modify(|v| *v += 1)
                 .or_insert(1);
         }
         if let Some(wild_size) = card_counts.remove(&Card::WildJack) {
             if let Some(&max) = card_counts.values().max() {
-                for (_, count) in card_counts.iter_mut() {
+                for count in card_counts.values_mut() {
                     if *count == max {
                         *count += wild_size;
                         break;
@@ -148,12 +165,14 @@ impl Ord for Hand {
     }
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
     hands: Vec<Hand>,
 }
 
 impl Game {
-    pub fn from_lines(lines: &Vec<String>, wild_jack: bool) -> Option<Game> {
+    pub fn from_lines(lines: &[String], wild_jack: bool) -> Option<Game> {
         let mut hands = lines
             .iter()
             .map(|line| Hand::from_line(line, wild_jack))
@@ -162,12 +181,27 @@ impl Game {
         Some(Game { hands })
     }
 
-    pub fn winnings(&self) -> usize {
-        self.hands
-            .iter()
-            .rev()
-            .enumerate()
-            .map(|(i, hand)| (i + 1) * hand.bid)
-            .sum()
+    pub fn winnings(&self) -> Option<usize> {
+        checked_sum_opt(
+            self.hands
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(i, hand)| (i + 1).checked_mul(hand.bid)),
+        )
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cards: String = self.cards.iter().map(|card| card.to_char()).collect();
+        write!(f, "{} {}", cards, self.bid)
+    }
+}
+
+// The hands are written in rank order, which is all the game keeps.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.hands, "\n")
     }
 }
@@ -1,5 +1,12 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+use crate::utils::{join, render};
+
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cave {
     rocks: HashSet<(i64, i64)>,
     width: i64,
@@ -61,21 +68,51 @@ impl Cave {
     }
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Caves {
     caves: Vec<Cave>,
 }
 
 impl Caves {
-    pub fn from_grids(grids: &Vec<HashMap<(i64, i64), char>>) -> Caves {
-        let caves = grids.iter().map(|grid| Cave::from_grid(grid)).collect();
+    pub fn from_grids(grids: &[HashMap<(i64, i64), char>]) -> Caves {
+        let caves = grids.iter().map(Cave::from_grid).collect();
         Caves { caves }
     }
 
     pub fn score(&self) -> usize {
-        self.caves.iter().map(|cave| cave.score(0)).sum()
+        self.score_all(0)
     }
 
     pub fn score_smudge(&self) -> usize {
-        self.caves.iter().map(|cave| cave.score(1)).sum()
+        self.score_all(1)
+    }
+
+    fn score_all(&self, num_fail: usize) -> usize {
+        #[cfg(feature = "parallel")]
+        let caves = self.caves.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let caves = self.caves.iter();
+        caves.map(|cave| cave.score(num_fail)).sum()
+    }
+}
+
+// width and height are the row and column counts.
+impl fmt::Display for Cave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = render(self.width, self.height, |i, j| {
+            if self.rocks.contains(&(i, j)) {
+                '#'
+            } else {
+                '.'
+            }
+        });
+        write!(f, "{}", grid)
+    }
+}
+
+impl fmt::Display for Caves {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.caves, "\n\n")
     }
 }
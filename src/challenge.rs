@@ -0,0 +1,419 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use super::answer::{checked, Answer, SolveError};
+use super::assumption::{require, Assumption};
+use super::control::Control;
+use super::*;
+
+fn challenge_1(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    checked(calibration::calibration_total(
+        &data,
+        &calibration::Lexicon::digits(),
+    ))
+}
+
+fn challenge_2(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    checked(calibration::calibration_total(
+        &data,
+        &calibration::Lexicon::english(),
+    ))
+}
+
+fn challenge_3(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let games = snow_game::Games::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(games.sum_possible(&snow_game::Bag::puzzle())?.into())
+}
+
+fn challenge_4(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let games = snow_game::Games::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(games.sum_power_set())
+}
+
+fn challenge_5(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let engine = parts::Engine::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(engine.sum_part_numbers())
+}
+
+fn challenge_6(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let engine = parts::Engine::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(engine.sum_gears())
+}
+
+fn challenge_7(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let cards = scratchcards::ScratchCards::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(cards.winnings())
+}
+
+fn challenge_8(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let cards = scratchcards::ScratchCards::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(cards.winning_scratchcards())
+}
+
+fn challenge_9(input: &str) -> Result<Answer, SolveError> {
+    let almanac = seeds::Almanac::from_string(input).ok_or(SolveError::Parse)?;
+    Ok(almanac.least_site().into())
+}
+
+fn challenge_10(input: &str) -> Result<Answer, SolveError> {
+    let almanac = seeds::Almanac::from_string(input).ok_or(SolveError::Parse)?;
+    let least = almanac.least_site_ranges().ok_or(SolveError::Parse)?;
+    Ok(least.into())
+}
+
+fn challenge_11(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let races = race::Races::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(races.score())
+}
+
+fn challenge_12(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let race = race::Race::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(race.num_success().into())
+}
+
+fn challenge_13(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let game = cards::Game::from_lines(&data, false).ok_or(SolveError::Parse)?;
+    checked(game.winnings())
+}
+
+fn challenge_14(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let game = cards::Game::from_lines(&data, true).ok_or(SolveError::Parse)?;
+    checked(game.winnings())
+}
+
+fn challenge_15(input: &str) -> Result<Answer, SolveError> {
+    let map = map::Map::from_string(input).ok_or(SolveError::Parse)?;
+    require(&map.steps_assumptions())?;
+    Ok(map.num_steps().ok_or(SolveError::Parse)?.into())
+}
+
+fn challenge_16(input: &str) -> Result<Answer, SolveError> {
+    let map = map::Map::from_string(input).ok_or(SolveError::Parse)?;
+    require(&map.ghost_assumptions())?;
+    checked(map.num_steps_ghost())
+}
+
+fn challenge_17(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let gens = sequences::SequenceGens::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(gens.next_sum())
+}
+
+fn challenge_18(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let gens = sequences::SequenceGens::from_lines(&data).ok_or(SolveError::Parse)?;
+    checked(gens.prev_sum())
+}
+
+fn challenge_19(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let pipes = pipes::Pipes::from_grid(&data).ok_or(SolveError::Parse)?;
+    require(&pipes.assumptions())?;
+    Ok(pipes.max_distance().into())
+}
+
+fn challenge_20(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let pipes = pipes::Pipes::from_grid(&data).ok_or(SolveError::Parse)?;
+    require(&pipes.assumptions())?;
+    Ok(pipes.enclosed().into())
+}
+
+fn challenge_21(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let galaxy = galaxy::Galaxy::from_grid(2, &data).ok_or(SolveError::Overflow)?;
+    checked(galaxy.sum_dist())
+}
+
+fn challenge_22(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let galaxy = galaxy::Galaxy::from_grid(1000000, &data).ok_or(SolveError::Overflow)?;
+    checked(galaxy.sum_dist())
+}
+
+fn challenge_25(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grids(input);
+    let caves = cave::Caves::from_grids(&data);
+    Ok(caves.score().into())
+}
+
+fn challenge_26(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grids(input);
+    let caves = cave::Caves::from_grids(&data);
+    Ok(caves.score_smudge().into())
+}
+
+fn challenge_27(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let mut beam = beams::Beam::from_grid(&data);
+    Ok(beam.score_north().into())
+}
+
+fn challenge_28(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let mut beam = beams::Beam::from_grid(&data);
+    Ok(beam.score_spin(1000000000).into())
+}
+
+fn challenge_29(input: &str) -> Result<Answer, SolveError> {
+    let commands = hash::Commands::from_line(input).ok_or(SolveError::Parse)?;
+    checked(commands.hash_sum())
+}
+
+fn challenge_30(input: &str) -> Result<Answer, SolveError> {
+    let boxes = hash::Boxes::from_line(input).ok_or(SolveError::Parse)?;
+    checked(boxes.focus_power_sum())
+}
+
+fn challenge_31(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let cave = mirrors::MirrorCave::from_grid(&data);
+    Ok(cave.simulate_excited().into())
+}
+
+fn challenge_32(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let cave = mirrors::MirrorCave::from_grid(&data);
+    Ok(cave.max_simulate_excited().into())
+}
+
+fn challenge_33(input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let city = crucible::City::from_grid(&data).ok_or(SolveError::Parse)?;
+    let least = city.least_path(1, 3, control)?;
+    Ok(least.into())
+}
+
+fn challenge_34(input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let city = crucible::City::from_grid(&data).ok_or(SolveError::Parse)?;
+    let least = city.least_path(4, 10, control)?;
+    Ok(least.into())
+}
+
+fn challenge_35(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let path = lake::Path::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(path.area().into())
+}
+
+fn challenge_35_shoelace(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let path = lake::Path::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(path.area_shoelace()?.into())
+}
+
+fn challenge_37(input: &str) -> Result<Answer, SolveError> {
+    let pile = workflow::Pile::from_string(input).ok_or(SolveError::Parse)?;
+    checked(pile.accepted_ratings())
+}
+
+fn challenge_38(input: &str) -> Result<Answer, SolveError> {
+    let pile = workflow::Pile::from_string(input).ok_or(SolveError::Parse)?;
+    checked(pile.accepted_combos())
+}
+
+fn challenge_39(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let mut network = signals::Network::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(network.count_signals(1000)?.into())
+}
+
+fn challenge_40(input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let mut network = signals::Network::from_lines(&data).ok_or(SolveError::Parse)?;
+    let presses = network.count_presses(control)?;
+    Ok(presses.into())
+}
+
+fn challenge_40_period(input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let mut network = signals::Network::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(network.count_presses_period(control)?.into())
+}
+
+fn challenge_41(input: &str) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let map = steps::Map::from_grid(&data).ok_or(SolveError::Parse)?;
+    Ok(map.steps(64).into())
+}
+
+fn challenge_43(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let mut bricks = bricks::Bricks::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(bricks.disintergrate_count().into())
+}
+
+fn challenge_44(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let mut bricks = bricks::Bricks::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(bricks.maximum_chain().into())
+}
+
+fn challenge_45(input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let woods = walk::Woods::from_grid(&data).ok_or(SolveError::Parse)?;
+    let longest = woods.maximum_path(false, control)?;
+    Ok(longest.into())
+}
+
+fn challenge_46(input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let data = io::grid(input);
+    let woods = walk::Woods::from_grid(&data).ok_or(SolveError::Parse)?;
+    let longest = woods.maximum_path(true, control)?;
+    Ok(longest.into())
+}
+
+fn challenge_47(input: &str) -> Result<Answer, SolveError> {
+    let data = io::lines(input);
+    let storm = hail::Storm::from_lines(&data).ok_or(SolveError::Parse)?;
+    Ok(storm
+        .intersect_in_area(200000000000000, 400000000000000 + 1)
+        .into())
+}
+
+type Solver = fn(&str, &Control) -> Result<Answer, SolveError>;
+
+// Challenges with more than one named implementation. The first listed is the
+// one challenge() runs, every other challenge has a single "default".
+const IMPLEMENTATIONS: [(u8, &str, Solver); 4] = [
+    (35, "flood_fill", |input, _| challenge_35(input)),
+    (35, "shoelace", |input, _| challenge_35_shoelace(input)),
+    (40, "period", challenge_40_period),
+    (40, "brute_force", challenge_40),
+];
+
+pub fn implementations(num: u8) -> Vec<&'static str> {
+    let names: Vec<&'static str> = IMPLEMENTATIONS
+        .iter()
+        .filter(|(n, _, _)| *n == num)
+        .map(|(_, name, _)| *name)
+        .collect();
+    if names.is_empty() {
+        vec!["default"]
+    } else {
+        names
+    }
+}
+
+pub fn challenge_impl(
+    num: u8,
+    name: &str,
+    input: &str,
+    control: &Control,
+) -> Result<Answer, SolveError> {
+    let found = IMPLEMENTATIONS
+        .iter()
+        .find(|(n, impl_name, _)| *n == num && *impl_name == name);
+    match found {
+        Some((_, _, solver)) => solver(input, control),
+        None if implementations(num) == ["default"] && name == "default" => {
+            challenge(num, input, control)
+        }
+        None => Err(SolveError::NoSolver),
+    }
+}
+
+// The input properties the solvers for a challenge rely on, checked against
+// an input. Includes those of every named implementation.
+pub fn assumptions(num: u8, input: &str) -> Result<Vec<Assumption>, SolveError> {
+    let input = io::normalize(input);
+    match num {
+        15 => Ok(map::Map::from_string(&input)
+            .ok_or(SolveError::Parse)?
+            .steps_assumptions()),
+        16 => Ok(map::Map::from_string(&input)
+            .ok_or(SolveError::Parse)?
+            .ghost_assumptions()),
+        19 | 20 => Ok(pipes::Pipes::from_grid(&io::grid(&input))
+            .ok_or(SolveError::Parse)?
+            .assumptions()),
+        35 => Ok(lake::Path::from_lines(&io::lines(&input))
+            .ok_or(SolveError::Parse)?
+            .shoelace_assumptions()),
+        40 => Ok(signals::Network::from_lines(&io::lines(&input))
+            .ok_or(SolveError::Parse)?
+            .period_assumptions()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+pub fn day(num: u8) -> i8 {
+    num.div_ceil(2) as i8
+}
+
+pub fn number(day: u8, part: u8) -> Option<u8> {
+    if day == 0 || !(1..=2).contains(&part) {
+        return None;
+    }
+    day.checked_sub(1)?.checked_mul(2)?.checked_add(part)
+}
+
+pub fn challenge(num: u8, input: &str, control: &Control) -> Result<Answer, SolveError> {
+    match num {
+        1 => challenge_1(input),
+        2 => challenge_2(input),
+        3 => challenge_3(input),
+        4 => challenge_4(input),
+        5 => challenge_5(input),
+        6 => challenge_6(input),
+        7 => challenge_7(input),
+        8 => challenge_8(input),
+        9 => challenge_9(input),
+        10 => challenge_10(input),
+        11 => challenge_11(input),
+        12 => challenge_12(input),
+        13 => challenge_13(input),
+        14 => challenge_14(input),
+        15 => challenge_15(input),
+        16 => challenge_16(input),
+        17 => challenge_17(input),
+        18 => challenge_18(input),
+        19 => challenge_19(input),
+        20 => challenge_20(input),
+        21 => challenge_21(input),
+        22 => challenge_22(input),
+        25 => challenge_25(input),
+        26 => challenge_26(input),
+        27 => challenge_27(input),
+        28 => challenge_28(input),
+        29 => challenge_29(input),
+        30 => challenge_30(input),
+        31 => challenge_31(input),
+        32 => challenge_32(input),
+        33 => challenge_33(input, control),
+        34 => challenge_34(input, control),
+        35 => challenge_35(input),
+        37 => challenge_37(input),
+        38 => challenge_38(input),
+        39 => challenge_39(input),
+        40 => challenge_40_period(input, control),
+        41 => challenge_41(input),
+        43 => challenge_43(input),
+        44 => challenge_44(input),
+        45 => challenge_45(input, control),
+        46 => challenge_46(input, control),
+        47 => challenge_47(input),
+        _ => Err(SolveError::NoSolver),
+    }
+}
+
+// Runs the challenge for a day and part on raw input text. Solver panics are
+// caught and reported as errors so callers outside Rust never see them.
+pub fn solve(day: u8, part: u8, input: &str, control: &Control) -> Result<Answer, SolveError> {
+    let num = number(day, part).ok_or(SolveError::NoSolver)?;
+    let input = io::normalize(input);
+    panic::catch_unwind(AssertUnwindSafe(|| challenge(num, &input, control)))
+        .unwrap_or(Err(SolveError::Panic))
+}
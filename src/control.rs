@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::answer::SolveError;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    pub done: usize,
+    pub total: Option<usize>,
+}
+
+type ProgressFn = Box<dyn Fn(Progress) + Send + Sync>;
+
+// Cooperative cancellation for long running solvers. Solvers call tick from
+// their main loop and give up (returning None) once it reports cancellation.
+pub struct Control {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    progress: Option<ProgressFn>,
+}
+
+impl Control {
+    pub fn new() -> Control {
+        Control {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: None,
+            progress: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Control {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_progress(mut self, progress: impl Fn(Progress) + Send + Sync + 'static) -> Control {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn has_progress(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancelled.clone()
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.deadline
+            .map(|deadline| Instant::now() >= deadline)
+            .unwrap_or(false)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.timed_out()
+    }
+
+    pub fn tick(&self, done: usize, total: Option<usize>) -> Option<()> {
+        if self.is_cancelled() {
+            return None;
+        }
+        if let Some(progress) = &self.progress {
+            progress(Progress { done, total });
+        }
+        Some(())
+    }
+
+    // The error to report when a solver gave up because of this control.
+    pub fn error(&self) -> SolveError {
+        if self.timed_out() {
+            SolveError::TimedOut
+        } else {
+            SolveError::Cancelled
+        }
+    }
+}
+
+impl Default for Control {
+    fn default() -> Control {
+        Control::new()
+    }
+}
+
+pub fn progress_bar(progress: Progress) {
+    const WIDTH: usize = 30;
+    match progress.total {
+        Some(total) if total > 0 => {
+            let filled = (progress.done.min(total) * WIDTH) / total;
+            eprint!(
+                "\r[{}{}] {}/{}",
+                "=".repeat(filled),
+                " ".repeat(WIDTH - filled),
+                progress.done,
+                total
+            );
+        }
+        _ => eprint!("\r{}", progress.done),
+    }
+}
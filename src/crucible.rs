@@ -1,34 +1,51 @@
 use std::cmp::min;
 use std::collections::HashMap;
+use std::fmt;
 
-use super::utils::Dir;
+use super::answer::SolveError;
+use super::control::Control;
+use super::utils::{render, Dir};
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct City {
+    #[cfg_attr(feature = "serde", serde(with = "crate::grid_serde"))]
     blocks: HashMap<(i64, i64), u8>,
     width: i64,
     height: i64,
 }
 
-fn u8_from_char(ch: char) -> u8 {
-    ch.to_digit(10).unwrap() as u8
+fn u8_from_char(ch: char) -> Option<u8> {
+    ch.to_digit(10).map(|d| d as u8)
 }
 
 impl City {
-    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> City {
+    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Option<City> {
         let blocks: HashMap<(i64, i64), u8> = grid
             .iter()
-            .map(|(ind, ch)| (*ind, u8_from_char(*ch)))
-            .collect();
+            .map(|(ind, ch)| Some((*ind, u8_from_char(*ch)?)))
+            .collect::<Option<HashMap<(i64, i64), u8>>>()?;
         let height = grid.iter().map(|((i, _), _)| i + 1).max().unwrap_or(0);
         let width = grid.iter().map(|((_, j), _)| j + 1).max().unwrap_or(0);
-        City {
+        // Every block has a heat loss, so the grid has no gaps.
+        if blocks.len() as i64 != height * width {
+            return None;
+        }
+        Some(City {
             blocks,
             width,
             height,
-        }
+        })
     }
 
-    pub fn least_path(&self, min_l: i64, max_l: i64) -> usize {
+    // Fails if cancelled before the path lengths settled, or if no route
+    // reaches the bottom right block.
+    pub fn least_path(
+        &self,
+        min_l: i64,
+        max_l: i64,
+        control: &Control,
+    ) -> Result<usize, SolveError> {
         let mut least_paths: HashMap<(i64, i64, Dir), usize> = HashMap::new();
         let end_i = self.height - 1;
         let end_j = self.width - 1;
@@ -36,7 +53,10 @@ impl City {
             least_paths.insert((end_i, end_j, dir), 0);
         }
         let mut updated = true;
+        let mut round = 0;
         while updated {
+            control.tick(round, None).ok_or(control.error())?;
+            round += 1;
             let (r_updated, r_least_paths) = self.least_paths_next(&least_paths, min_l, max_l);
             updated = r_updated;
             least_paths = r_least_paths;
@@ -47,7 +67,10 @@ impl City {
                 min_val = min(min_val, *val);
             }
         }
-        min_val
+        if min_val == usize::MAX {
+            return Err(SolveError::NoAnswer);
+        }
+        Ok(min_val)
     }
 
     fn least_paths_next(
@@ -85,17 +108,39 @@ impl City {
     }
 
     fn go(&self, i: i64, j: i64, dir: Dir, dist: i64) -> Option<(i64, i64, usize)> {
-        let mut n_i = i;
-        let mut n_j = j;
+        let (mut i, mut j) = (i, j);
         let mut loss = 0;
         for _ in 0..dist {
-            (n_i, n_j) = dir.step(n_i, n_j);
-            if let Some(block) = self.blocks.get(&(n_i, n_j)) {
-                loss += *block as usize;
-            } else {
-                return None;
-            }
+            (i, j) = dir.step(i, j);
+            loss += *self.blocks.get(&(i, j))? as usize;
         }
-        Some((n_i, n_j, loss))
+        Some((i, j, loss))
+    }
+}
+
+impl fmt::Display for City {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = render(self.height, self.width, |i, j| {
+            self.blocks
+                .get(&(i, j))
+                .map_or('.', |block| (b'0' + block) as char)
+        });
+        write!(f, "{}", grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io;
+
+    #[test]
+    fn a_goal_too_close_for_the_ultra_crucible_has_no_answer() {
+        let city = City::from_grid(&io::grid("11")).unwrap();
+        assert_eq!(city.least_path(1, 3, &Control::new()), Ok(1));
+        assert_eq!(
+            city.least_path(4, 10, &Control::new()),
+            Err(SolveError::NoAnswer)
+        );
     }
 }
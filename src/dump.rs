@@ -0,0 +1,36 @@
+use super::*;
+use serde::Serialize;
+
+fn json<T: Serialize>(model: &T) -> Option<String> {
+    serde_json::to_string_pretty(model).ok()
+}
+
+pub fn dump_json(day: i8) -> Option<String> {
+    let input = io::read_input(day, false).ok()?;
+    match day {
+        1 => json(&io::lines(&input)),
+        2 => json(&snow_game::Games::from_lines(&io::lines(&input))?),
+        3 => json(&parts::Engine::from_lines(&io::lines(&input))?),
+        4 => json(&scratchcards::ScratchCards::from_lines(&io::lines(&input))?),
+        5 => json(&seeds::Almanac::from_string(&input)?),
+        6 => json(&race::Races::from_lines(&io::lines(&input))?),
+        7 => json(&cards::Game::from_lines(&io::lines(&input), false)?),
+        8 => json(&map::Map::from_string(&input)?),
+        9 => json(&sequences::SequenceGens::from_lines(&io::lines(&input))?),
+        10 => json(&pipes::Pipes::from_grid(&io::grid(&input))?),
+        11 => json(&galaxy::Galaxy::from_grid(2, &io::grid(&input))?),
+        13 => json(&cave::Caves::from_grids(&io::grids(&input))),
+        14 => json(&beams::Beam::from_grid(&io::grid(&input))),
+        15 => json(&hash::Commands::from_line(&input)?),
+        16 => json(&mirrors::MirrorCave::from_grid(&io::grid(&input))),
+        17 => json(&crucible::City::from_grid(&io::grid(&input))?),
+        18 => json(&lake::Path::from_lines(&io::lines(&input))?),
+        19 => json(&workflow::Pile::from_string(&input)?),
+        20 => json(&signals::Network::from_lines(&io::lines(&input))?),
+        21 => json(&steps::Map::from_grid(&io::grid(&input))?),
+        22 => json(&bricks::Bricks::from_lines(&io::lines(&input))?),
+        23 => json(&walk::Woods::from_grid(&io::grid(&input))?),
+        24 => json(&hail::Storm::from_lines(&io::lines(&input))?),
+        _ => None,
+    }
+}
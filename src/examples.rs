@@ -0,0 +1,207 @@
+use crate::answer::SolveError;
+use crate::challenge;
+use crate::control::Control;
+use std::fs;
+
+// An example lifted from a saved puzzle page: the input shown for a part and
+// the answer the page emphasises for it.
+pub struct Example {
+    pub part: u8,
+    pub input: String,
+    pub answer: Option<String>,
+}
+
+pub enum Outcome {
+    Pass,
+    Fail(String),
+    Error(SolveError),
+}
+
+fn decode(text: &str) -> String {
+    let mut plain = String::new();
+    let mut in_tag = false;
+    for ch in text.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(ch),
+            _ => {}
+        }
+    }
+    plain
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// Every (position, contents) between open and close, in order.
+fn between<'a>(text: &'a str, open: &str, close: &str) -> Vec<(usize, &'a str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find(open) {
+        let inner = from + start + open.len();
+        let Some(len) = text[inner..].find(close) else {
+            break;
+        };
+        found.push((inner, &text[inner..inner + len]));
+        from = inner + len + close.len();
+    }
+    found
+}
+
+// Whether the answer at pos is stated for other parameters than the part
+// asks for, such as a smaller expansion, which the page phrases as what the
+// answer would be.
+fn hypothetical(article: &str, pos: usize) -> bool {
+    let before = &article[..pos];
+    let sentence = before
+        .rfind(". ")
+        .into_iter()
+        .chain(before.rfind("<p>"))
+        .max()
+        .unwrap_or(0);
+    before[sentence..].contains("would")
+}
+
+// Each part is an <article>. The example is the first code block after "For
+// example", as later blocks tend to show the example part way through being
+// solved, and the expected answer the last emphasised code span not stated
+// for other parameters. Part two usually reuses the example from part one, so
+// it carries over when a part introduces no example of its own.
+pub fn extract(html: &str) -> Vec<Example> {
+    let mut examples: Vec<Example> = Vec::new();
+    for (i, article) in html.split("<article").skip(1).enumerate() {
+        let article = article.split("</article>").next().unwrap_or(article);
+        let blocks = between(article, "<pre><code>", "</code></pre>");
+        let block = match article.find("For example") {
+            Some(intro) => blocks.iter().find(|(pos, _)| *pos > intro),
+            None if examples.is_empty() => blocks.first(),
+            None => None,
+        };
+        let input = block
+            .map(|(_, block)| decode(block).trim_end().to_string())
+            .or_else(|| examples.last().map(|example| example.input.clone()));
+        let mut answers = between(article, "<code><em>", "</em></code>");
+        answers.extend(between(article, "<em><code>", "</code></em>"));
+        let answer = answers
+            .iter()
+            .filter(|(pos, _)| !hypothetical(article, *pos))
+            .max_by_key(|(pos, _)| *pos)
+            .map(|(_, answer)| decode(answer));
+        if let Some(input) = input {
+            examples.push(Example {
+                part: i as u8 + 1,
+                input,
+                answer,
+            });
+        }
+    }
+    examples
+}
+
+fn fixture(day: u8, part: u8, ext: &str) -> String {
+    format!("data/examples/day-{}-{}.{}", day, part, ext)
+}
+
+// Writes the examples of a saved puzzle page next to the puzzle inputs and
+// returns the files written.
+pub fn import(day: u8, html_file: &str) -> Result<Vec<String>, String> {
+    let html = fs::read_to_string(html_file).map_err(|err| err.to_string())?;
+    let examples = extract(&html);
+    if examples.is_empty() {
+        return Err(format!("no examples found in {}", html_file));
+    }
+    fs::create_dir_all("data/examples").map_err(|err| err.to_string())?;
+    let mut written = Vec::new();
+    for example in examples {
+        let input_file = fixture(day, example.part, "txt");
+        fs::write(&input_file, format!("{}\n", example.input)).map_err(|err| err.to_string())?;
+        written.push(input_file);
+        if let Some(answer) = example.answer {
+            let answer_file = fixture(day, example.part, "answer");
+            fs::write(&answer_file, format!("{}\n", answer)).map_err(|err| err.to_string())?;
+            written.push(answer_file);
+        }
+    }
+    Ok(written)
+}
+
+// Runs the solvers for a day against its imported examples. A hand-written
+// .expected file overrides the answer taken from the page, which import never
+// touches. Parts without a fixture or an expected answer are skipped.
+pub fn check(day: u8, control: &Control) -> Vec<(u8, Outcome)> {
+    let mut outcomes = Vec::new();
+    for part in 1..=2 {
+        let input = fs::read_to_string(fixture(day, part, "txt"));
+        let answer = fs::read_to_string(fixture(day, part, "expected"))
+            .or_else(|_| fs::read_to_string(fixture(day, part, "answer")));
+        let (Ok(input), Ok(answer)) = (input, answer) else {
+            continue;
+        };
+        let outcome = match challenge::solve(day, part, &input, control) {
+            Ok(got) if got.to_string() == answer.trim() => Outcome::Pass,
+            Ok(got) => Outcome::Fail(got.to_string()),
+            Err(err) => Outcome::Error(err),
+        };
+        outcomes.push((part, outcome));
+    }
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_the_example_not_a_later_state() {
+        let html = fs::read_to_string("data/pages/day-11.html").unwrap();
+        let examples = extract(&html);
+        assert_eq!(examples.len(), 2);
+        let input = &examples[0].input;
+        assert_eq!(input.lines().count(), 10);
+        assert!(input.starts_with("...#......\n.......#.."));
+        assert_eq!(examples[0].answer.as_deref(), Some("374"));
+        assert_eq!(examples[1].input, *input);
+        // 1030 and 8410 are for smaller expansions than part two asks for.
+        assert_eq!(examples[1].answer, None);
+    }
+
+    #[test]
+    fn solves_the_extracted_example() {
+        let html = fs::read_to_string("data/pages/day-11.html").unwrap();
+        let example = &extract(&html)[0];
+        let answer = challenge::solve(11, 1, &example.input, &Control::new());
+        assert_eq!(
+            answer.map(|answer| answer.to_string()),
+            Ok("374".to_string())
+        );
+    }
+
+    #[test]
+    fn every_stored_fixture_passes() {
+        let mut days: Vec<u8> = fs::read_dir("data/examples")
+            .unwrap()
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_prefix("day-")?.split('-').next()?.parse().ok()
+            })
+            .collect();
+        days.sort();
+        days.dedup();
+        assert!(!days.is_empty());
+        for day in days {
+            let outcomes = check(day, &Control::new());
+            assert!(!outcomes.is_empty(), "day {} has no answers", day);
+            for (part, outcome) in outcomes {
+                match outcome {
+                    Outcome::Pass => {}
+                    Outcome::Fail(got) => panic!("day {} part {} got {}", day, part, got),
+                    Outcome::Error(err) => panic!("day {} part {}: {}", day, part, err),
+                }
+            }
+        }
+    }
+}
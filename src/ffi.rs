@@ -0,0 +1,87 @@
+use std::ffi::{c_char, CString};
+use std::slice;
+
+use super::answer::SolveError;
+use super::challenge;
+use super::control::Control;
+
+pub const SOLVE_OK: i32 = 0;
+pub const SOLVE_PARSE: i32 = 1;
+pub const SOLVE_OVERFLOW: i32 = 2;
+pub const SOLVE_NO_SOLVER: i32 = 3;
+pub const SOLVE_TIMED_OUT: i32 = 4;
+pub const SOLVE_CANCELLED: i32 = 5;
+pub const SOLVE_PANIC: i32 = 6;
+pub const SOLVE_INVALID_INPUT: i32 = 7;
+pub const SOLVE_ASSUMPTION: i32 = 8;
+pub const SOLVE_NO_ANSWER: i32 = 9;
+
+// On success text holds the answer, otherwise a description of the error.
+// Either way it must be released with aoc_solve_free.
+#[repr(C)]
+pub struct SolveResult {
+    pub status: i32,
+    pub text: *mut c_char,
+}
+
+fn status(err: &SolveError) -> i32 {
+    match err {
+        SolveError::Parse => SOLVE_PARSE,
+        SolveError::Overflow => SOLVE_OVERFLOW,
+        SolveError::NoSolver => SOLVE_NO_SOLVER,
+        SolveError::TimedOut => SOLVE_TIMED_OUT,
+        SolveError::Cancelled => SOLVE_CANCELLED,
+        SolveError::Panic => SOLVE_PANIC,
+        SolveError::Assumption => SOLVE_ASSUMPTION,
+        SolveError::NoAnswer => SOLVE_NO_ANSWER,
+    }
+}
+
+fn result(status: i32, text: String) -> SolveResult {
+    // Answers and error messages never contain NUL bytes.
+    let text = CString::new(text).unwrap_or_default();
+    SolveResult {
+        status,
+        text: text.into_raw(),
+    }
+}
+
+/// Solves one part of a day for the given UTF-8 puzzle input.
+///
+/// # Safety
+///
+/// `input_ptr` must point to `len` readable bytes, or be null when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(
+    day: u8,
+    part: u8,
+    input_ptr: *const u8,
+    len: usize,
+) -> SolveResult {
+    let bytes = if len == 0 {
+        &[]
+    } else if input_ptr.is_null() {
+        return result(SOLVE_INVALID_INPUT, "input pointer is null".to_string());
+    } else {
+        slice::from_raw_parts(input_ptr, len)
+    };
+    let Ok(input) = std::str::from_utf8(bytes) else {
+        return result(SOLVE_INVALID_INPUT, "input is not valid UTF-8".to_string());
+    };
+    match challenge::solve(day, part, input, &Control::new()) {
+        Ok(answer) => result(SOLVE_OK, answer.to_string()),
+        Err(err) => result(status(&err), err.to_string()),
+    }
+}
+
+/// Releases the text of a result returned by aoc_solve.
+///
+/// # Safety
+///
+/// `result` must come from aoc_solve and must not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve_free(result: SolveResult) {
+    if !result.text.is_null() {
+        drop(CString::from_raw(result.text));
+    }
+}
@@ -1,69 +1,117 @@
+use crate::answer::checked_sum;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
 
+use crate::utils::render;
+
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Galaxy {
     stars: HashSet<(i64, i64)>,
 }
 
-fn expand(expansion: usize, vals: &BTreeSet<i64>) -> HashMap<i64, i64> {
-    let mut prev = vals.first().unwrap() - 1;
-    let multiplier = (expansion - 1) as i64;
-    let mut offset = 0;
+fn expand(expansion: usize, vals: &BTreeSet<i64>) -> Option<HashMap<i64, i64>> {
+    let multiplier = i64::try_from(expansion.checked_sub(1)?).ok()?;
+    let mut offset = 0_i64;
     let mut map: HashMap<i64, i64> = HashMap::new();
+    let Some(first) = vals.first() else {
+        return Some(map);
+    };
+    let mut prev = first - 1;
     for val in vals {
         if *val != prev + 1 {
             offset += (val - prev) - 1;
         }
-        map.insert(*val, val + offset * multiplier);
+        map.insert(*val, val.checked_add(offset.checked_mul(multiplier)?)?);
         prev = *val;
     }
-    map
+    Some(map)
 }
 
 impl Galaxy {
-    pub fn from_grid(expansion: usize, grid: &HashMap<(i64, i64), char>) -> Galaxy {
+    // None if the expanded coordinates do not fit in an i64.
+    pub fn from_grid(expansion: usize, grid: &HashMap<(i64, i64), char>) -> Option<Galaxy> {
         let stars = grid
             .iter()
             .filter(|(_, ch)| **ch == '#')
             .map(|(id, _)| *id)
             .collect();
-        (Galaxy { stars }).expand_x(expansion).expand_y(expansion)
+        (Galaxy { stars }).expand_x(expansion)?.expand_y(expansion)
+    }
+
+    // Each star's distances are summed independently, across threads with the
+    // parallel feature. All the partial sums are unsigned, so one overflows
+    // exactly when the total would.
+    pub fn sum_dist(&self) -> Option<u64> {
+        let stars: Vec<&(i64, i64)> = self.stars.iter().collect();
+        #[cfg(feature = "parallel")]
+        let sum = stars
+            .par_iter()
+            .map(|star| self.sum_dist_from(star))
+            .try_reduce(|| 0, |a, b| a.checked_add(b));
+        #[cfg(not(feature = "parallel"))]
+        let sum = stars
+            .iter()
+            .map(|star| self.sum_dist_from(star))
+            .try_fold(0_u64, |a, b| a.checked_add(b?));
+        sum
     }
 
-    pub fn sum_dist(&self) -> usize {
-        let mut sum: usize = 0;
-        for star1 in &self.stars {
-            for star2 in &self.stars {
-                if *star1 < *star2 {
-                    sum += self.dist(star1, star2);
-                }
+    // Distances to the stars ordered after this one, so each pair counts once.
+    fn sum_dist_from(&self, star1: &(i64, i64)) -> Option<u64> {
+        let mut sum: u64 = 0;
+        for star2 in &self.stars {
+            if *star1 < *star2 {
+                sum = sum.checked_add(self.dist(star1, star2)?)?;
             }
         }
-        sum
+        Some(sum)
     }
 
-    fn dist(&self, star1: &(i64, i64), star2: &(i64, i64)) -> usize {
-        (star2.0 - star1.0).abs() as usize + (star2.1 - star1.1).abs() as usize
+    fn dist(&self, star1: &(i64, i64), star2: &(i64, i64)) -> Option<u64> {
+        checked_sum([
+            star2.0.checked_sub(star1.0)?.unsigned_abs(),
+            star2.1.checked_sub(star1.1)?.unsigned_abs(),
+        ])
     }
 
-    fn expand_x(&self, expansion: usize) -> Galaxy {
+    fn expand_x(&self, expansion: usize) -> Option<Galaxy> {
         let x_vals: BTreeSet<i64> = self.stars.iter().map(|(x, _)| *x).collect();
-        let map = expand(expansion, &x_vals);
+        let map = expand(expansion, &x_vals)?;
         let new_stars = self
             .stars
             .iter()
             .map(|(x, y)| (*map.get(x).unwrap(), *y))
             .collect();
-        Galaxy { stars: new_stars }
+        Some(Galaxy { stars: new_stars })
     }
 
-    fn expand_y(&self, expansion: usize) -> Galaxy {
+    fn expand_y(&self, expansion: usize) -> Option<Galaxy> {
         let y_vals: BTreeSet<i64> = self.stars.iter().map(|(_, y)| *y).collect();
-        let map = expand(expansion, &y_vals);
+        let map = expand(expansion, &y_vals)?;
         let new_stars = self
             .stars
             .iter()
             .map(|(x, y)| (*x, *map.get(y).unwrap()))
             .collect();
-        Galaxy { stars: new_stars }
+        Some(Galaxy { stars: new_stars })
+    }
+}
+
+// The stars are kept already expanded, so this is the expanded image.
+impl fmt::Display for Galaxy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let height = self.stars.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+        let width = self.stars.iter().map(|(_, j)| j + 1).max().unwrap_or(0);
+        let grid = render(height, width, |i, j| {
+            if self.stars.contains(&(i, j)) {
+                '#'
+            } else {
+                '.'
+            }
+        });
+        write!(f, "{}", grid)
     }
 }
@@ -1,15 +1,26 @@
 use num::rational::Ratio;
 use num::Zero;
+use std::fmt;
 
+use crate::utils::join;
+
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Hail {
     pos: (i64, i64, i64),
     vel: (i64, i64, i64),
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Storm {
     hail: Vec<Hail>,
 }
 
+// Bounds keeping the exact i128 intersection maths clear of overflow.
+const MAX_POS: i64 = 1 << 50;
+const MAX_VEL: i64 = 1 << 20;
+
 fn vec3(string: &str) -> Option<(i64, i64, i64)> {
     let mut parts = string.split(", ");
     let x = parts
@@ -25,7 +36,7 @@ fn vec3(string: &str) -> Option<(i64, i64, i64)> {
 }
 
 impl Storm {
-    pub fn from_lines(lines: &Vec<String>) -> Option<Storm> {
+    pub fn from_lines(lines: &[String]) -> Option<Storm> {
         let hail = lines
             .iter()
             .map(|line| Hail::from_line(line))
@@ -41,12 +52,12 @@ impl Storm {
         for (i, hail1) in self.hail.iter().enumerate() {
             for hail2 in self.hail.iter().skip(i) {
                 if let Some((t, s)) = hail1.intersect(hail2) {
-                    if t >= rat_zero && s >= rat_zero {
-                        if hail1.in_area(t, area_min, area_max)
-                            && hail2.in_area(t, area_min, area_max)
-                        {
-                            count += 1;
-                        }
+                    if t >= rat_zero
+                        && s >= rat_zero
+                        && hail1.in_area(t, area_min, area_max)
+                        && hail2.in_area(t, area_min, area_max)
+                    {
+                        count += 1;
                     }
                 }
             }
@@ -60,6 +71,12 @@ impl Hail {
         let (pos_s, vec_s) = line.split_once(" @ ")?;
         let pos = vec3(pos_s)?;
         let vel = vec3(vec_s)?;
+        let in_bounds = |(x, y, z): (i64, i64, i64), bound: i64| {
+            [x, y, z].iter().all(|v| v.unsigned_abs() < bound as u64)
+        };
+        if !in_bounds(pos, MAX_POS) || !in_bounds(vel, MAX_VEL) {
+            return None;
+        }
         Some(Hail { pos, vel })
     }
 
@@ -103,3 +120,16 @@ impl Hail {
         (x, y, z)
     }
 }
+
+impl fmt::Display for Hail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (p, v) = (self.pos, self.vel);
+        write!(f, "{}, {}, {} @ {}, {}, {}", p.0, p.1, p.2, v.0, v.1, v.2)
+    }
+}
+
+impl fmt::Display for Storm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.hail, "\n")
+    }
+}
@@ -1,28 +1,34 @@
+use crate::answer::{checked_product, checked_sum};
+use crate::utils::join;
+use std::fmt;
+
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Commands {
     commands: Vec<String>,
 }
 
 fn hash(command: &str) -> u8 {
     let mut val = 0_usize;
-    for char in command.chars() {
-        val += char.as_ascii().unwrap().to_u8() as usize;
+    for byte in command.bytes() {
+        val += byte as usize;
         val *= 17;
-        val = val % 256;
+        val %= 256;
     }
     val as u8
 }
 
 impl Commands {
-    pub fn from_line(line: &String) -> Commands {
+    pub fn from_line(line: &str) -> Option<Commands> {
+        if !line.is_ascii() {
+            return None;
+        }
         let commands = line.split(",").map(|part| part.to_string()).collect();
-        Commands { commands }
+        Some(Commands { commands })
     }
 
-    pub fn hash_sum(&self) -> usize {
-        self.commands
-            .iter()
-            .map(|command| hash(command) as usize)
-            .sum()
+    pub fn hash_sum(&self) -> Option<usize> {
+        checked_sum(self.commands.iter().map(|command| hash(command) as usize))
     }
 }
 
@@ -36,19 +42,22 @@ pub struct Boxes {
 }
 
 impl Boxes {
-    pub fn from_line(line: &String) -> Boxes {
+    pub fn from_line(line: &str) -> Option<Boxes> {
+        if !line.is_ascii() {
+            return None;
+        }
         let init: [Vec<Lens>; 256] = [const { Vec::new() }; 256];
         let mut boxes = Boxes { boxes: init };
         for command in line.split(",") {
             if let Some(label) = command.strip_suffix("-") {
                 boxes.subtract(label);
             } else {
-                let (label, focus_str) = command.split_once("=").unwrap();
-                let focus = focus_str.parse::<usize>().unwrap();
+                let (label, focus_str) = command.split_once("=")?;
+                let focus = focus_str.parse::<usize>().ok()?;
                 boxes.add(label, focus);
             }
         }
-        boxes
+        Some(boxes)
     }
 
     fn subtract(&mut self, label: &str) {
@@ -74,13 +83,31 @@ impl Boxes {
         })
     }
 
-    pub fn focus_power_sum(&self) -> usize {
+    pub fn focus_power_sum(&self) -> Option<usize> {
         let mut total = 0usize;
         for (i, light_box) in self.boxes.iter().enumerate() {
             for (j, lens) in light_box.iter().enumerate() {
-                total += (i + 1) * (j + 1) * lens.focus;
+                total = total.checked_add(checked_product([i + 1, j + 1, lens.focus])?)?;
             }
         }
-        total
+        Some(total)
+    }
+}
+
+impl fmt::Display for Commands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.commands, ",")
+    }
+}
+
+// Written as the steps that put each lens in place, box by box.
+impl fmt::Display for Boxes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let steps = self
+            .boxes
+            .iter()
+            .flatten()
+            .map(|lens| format!("{}={}", lens.label, lens.focus));
+        join(f, steps, ",")
     }
 }
@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::answer::{Answer, SolveError};
+
+const HISTORY_FILE: &str = "data/history.jsonl";
+
+// One runner invocation, appended as a line of JSON to the history file.
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub timestamp: u64,
+    pub challenge: u8,
+    #[serde(default = "default_implementation")]
+    pub implementation: String,
+    pub revision: Option<String>,
+    pub input_hash: String,
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    pub time_ms: f64,
+}
+
+pub struct Change {
+    pub challenge: u8,
+    pub implementation: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+pub struct Slowdown {
+    pub challenge: u8,
+    pub implementation: String,
+    pub before_ms: f64,
+    pub after_ms: f64,
+}
+
+pub struct Diff {
+    pub changed: Vec<Change>,
+    pub slower: Vec<Slowdown>,
+    // Challenges and implementations run at only the later or earlier
+    // revision.
+    pub added: Vec<(u8, String)>,
+    pub removed: Vec<(u8, String)>,
+}
+
+// FNV-1a, which unlike the std hasher is stable between builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn default_implementation() -> String {
+    "default".to_string()
+}
+
+fn revision() -> Option<String> {
+    let output = Command::new("git")
+        .args(["describe", "--always", "--dirty", "--abbrev=12"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
+
+impl Record {
+    pub fn new(
+        challenge: u8,
+        implementation: &str,
+        input: &str,
+        result: &Result<Answer, SolveError>,
+        elapsed: Duration,
+    ) -> Record {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0);
+        Record {
+            timestamp,
+            challenge,
+            implementation: implementation.to_string(),
+            revision: revision(),
+            input_hash: format!("{:016x}", fnv1a(input.as_bytes())),
+            answer: result.as_ref().ok().map(|answer| answer.to_string()),
+            error: result.as_ref().err().map(|err| err.to_string()),
+            time_ms: elapsed.as_secs_f64() * 1000.0,
+        }
+    }
+
+    fn outcome(&self) -> Option<String> {
+        self.answer
+            .clone()
+            .or_else(|| self.error.as_ref().map(|err| format!("error: {}", err)))
+    }
+}
+
+pub fn append(record: &Record) -> Result<(), String> {
+    let line = serde_json::to_string(record).map_err(|err| err.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_FILE)
+        .map_err(|err| err.to_string())?;
+    writeln!(file, "{}", line).map_err(|err| err.to_string())
+}
+
+// Lines that fail to parse are skipped, so a torn write does not lose the rest.
+pub fn load() -> Vec<Record> {
+    fs::read_to_string(HISTORY_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+// The latest record of each challenge and implementation run at a revision,
+// matched by prefix.
+fn latest_at<'a>(records: &'a [Record], revision: &str) -> BTreeMap<(u8, &'a str), &'a Record> {
+    let mut latest = BTreeMap::new();
+    for record in records {
+        if record
+            .revision
+            .as_ref()
+            .is_some_and(|rev| rev.starts_with(revision))
+        {
+            latest.insert((record.challenge, record.implementation.as_str()), record);
+        }
+    }
+    latest
+}
+
+// Compares challenges run at both revisions. Answers only count as changed
+// when the input was the same, and a challenge counts as slower when it took
+// over a tenth longer, ignoring sub-millisecond noise.
+pub fn diff(records: &[Record], a: &str, b: &str) -> Diff {
+    let before = latest_at(records, a);
+    let after = latest_at(records, b);
+    let mut changed = Vec::new();
+    let mut slower = Vec::new();
+    let mut removed = Vec::new();
+    for (key, old) in &before {
+        let Some(new) = after.get(key) else {
+            removed.push((key.0, key.1.to_string()));
+            continue;
+        };
+        if old.input_hash == new.input_hash && old.outcome() != new.outcome() {
+            changed.push(Change {
+                challenge: old.challenge,
+                implementation: old.implementation.clone(),
+                before: old.outcome(),
+                after: new.outcome(),
+            });
+        }
+        if new.time_ms > old.time_ms * 1.1 + 1.0 {
+            slower.push(Slowdown {
+                challenge: old.challenge,
+                implementation: old.implementation.clone(),
+                before_ms: old.time_ms,
+                after_ms: new.time_ms,
+            });
+        }
+    }
+    let added = after
+        .keys()
+        .filter(|key| !before.contains_key(key))
+        .map(|(challenge, implementation)| (*challenge, implementation.to_string()))
+        .collect();
+    Diff {
+        changed,
+        slower,
+        added,
+        removed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(challenge: u8, revision: &str, answer: &str, time_ms: f64) -> Record {
+        Record {
+            timestamp: 0,
+            challenge,
+            implementation: "default".to_string(),
+            revision: Some(revision.to_string()),
+            input_hash: "0".to_string(),
+            answer: Some(answer.to_string()),
+            error: None,
+            time_ms,
+        }
+    }
+
+    #[test]
+    fn diffs_added_removed_and_changed_runs() {
+        let mut period = run(40, "bbb", "7", 2.0);
+        period.implementation = "period".to_string();
+        let mut other_input = run(3, "bbb", "9", 1.0);
+        other_input.input_hash = "1".to_string();
+        let records = [
+            run(1, "aaa", "5", 1.0),
+            run(2, "aaa", "6", 1.0),
+            run(3, "aaa", "8", 1.0),
+            run(40, "aaa", "7", 1.0),
+            run(1, "bbb", "5", 1.0),
+            run(1, "aaa", "4", 1.0),
+            other_input,
+            run(40, "bbb", "7", 5.0),
+            period,
+        ];
+        let diff = diff(&records, "aaa", "bbb");
+        // The latest run at a revision counts, and only on the same input.
+        let changed: Vec<_> = diff
+            .changed
+            .iter()
+            .map(|change| {
+                (
+                    change.challenge,
+                    change.before.clone(),
+                    change.after.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(changed, [(1, Some("4".to_string()), Some("5".to_string()))]);
+        assert_eq!(diff.changed[0].implementation, "default");
+        let slower: Vec<_> = diff
+            .slower
+            .iter()
+            .map(|slowdown| (slowdown.challenge, slowdown.implementation.as_str()))
+            .collect();
+        assert_eq!(slower, [(40, "default")]);
+        assert_eq!(diff.added, [(40, "period".to_string())]);
+        assert_eq!(diff.removed, [(2, "default".to_string())]);
+    }
+
+    #[test]
+    fn reports_errors_as_changes() {
+        let mut failed = run(1, "bbb", "", 1.0);
+        failed.answer = None;
+        failed.error = Some("input has no answer".to_string());
+        let diff = diff(&[run(1, "aaa", "5", 1.0), failed], "a", "b");
+        assert_eq!(
+            diff.changed[0].after.as_deref(),
+            Some("error: input has no answer")
+        );
+    }
+}
@@ -1,23 +1,111 @@
 use std::collections::HashMap;
-use std::fs::{read_to_string, File};
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use std::fs::read_to_string;
 
-pub fn input_as_lines(day: i8) -> Vec<String> {
-    let filename = format!("data/day-{}.txt", day);
-    let file = File::open(filename).expect("Issue opening file");
-    let reader = BufReader::new(&file);
-    reader.lines().map(|s| s.expect("Read failure")).collect()
+pub type Grid = HashMap<(i64, i64), char>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Irregularity {
+    ByteOrderMark,
+    CarriageReturn,
+    TrailingWhitespace,
+    TrailingBlankLine,
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Read(String),
+    Irregular(Vec<(usize, Irregularity)>),
+}
+
+impl fmt::Display for Irregularity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Irregularity::ByteOrderMark => write!(f, "byte order mark"),
+            Irregularity::CarriageReturn => write!(f, "carriage return"),
+            Irregularity::TrailingWhitespace => write!(f, "trailing whitespace"),
+            Irregularity::TrailingBlankLine => write!(f, "trailing blank line"),
+        }
+    }
 }
 
-pub fn input_as_string(day: i8) -> String {
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Read(err) => write!(f, "could not read input: {}", err),
+            InputError::Irregular(found) => {
+                write!(f, "irregular whitespace in input")?;
+                for (line, irregularity) in found {
+                    write!(f, "\n  line {}: {}", line, irregularity)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// Lists whitespace that normalize would silently fix, with 1-based line numbers.
+pub fn irregularities(text: &str) -> Vec<(usize, Irregularity)> {
+    let mut found = Vec::new();
+    if text.starts_with('\u{feff}') {
+        found.push((1, Irregularity::ByteOrderMark));
+    }
+    let lines: Vec<&str> = text.split('\n').collect();
+    for (i, line) in lines.iter().enumerate() {
+        if line.contains('\r') {
+            found.push((i + 1, Irregularity::CarriageReturn));
+        }
+        let content = line.trim_end_matches('\r');
+        if content.trim_end() != content {
+            found.push((i + 1, Irregularity::TrailingWhitespace));
+        }
+    }
+    // A single final newline is expected, anything after it is a blank line.
+    let content_lines = lines.len() - usize::from(text.ends_with('\n'));
+    for i in (0..content_lines).rev() {
+        if !lines[i].trim().is_empty() {
+            break;
+        }
+        found.push((i + 1, Irregularity::TrailingBlankLine));
+    }
+    found.sort();
+    found
+}
+
+// Strips a byte order mark, converts line endings to \n, trims trailing
+// whitespace from each line and drops trailing blank lines. The result has no
+// final newline.
+pub fn normalize(text: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = text.split('\n').map(|line| line.trim_end()).collect();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    lines[..end].join("\n")
+}
+
+pub fn read_input(day: i8, strict: bool) -> Result<String, InputError> {
     let filename = format!("data/day-{}.txt", day);
-    read_to_string(filename).expect("Read failure")
+    let text = read_to_string(filename).map_err(|err| InputError::Read(err.to_string()))?;
+    if strict {
+        let found = irregularities(&text);
+        if !found.is_empty() {
+            return Err(InputError::Irregular(found));
+        }
+    }
+    Ok(normalize(&text))
+}
+
+pub fn lines(input: &str) -> Vec<String> {
+    input.lines().map(|line| line.to_string()).collect()
 }
 
-pub fn input_as_grid(day: i8) -> HashMap<(i64, i64), char> {
-    let mut grid: HashMap<(i64, i64), char> = HashMap::new();
-    for (i, line) in input_as_lines(day).into_iter().enumerate() {
+pub fn grid(input: &str) -> Grid {
+    let mut grid: Grid = HashMap::new();
+    for (i, line) in input.lines().enumerate() {
         for (j, ch) in line.chars().enumerate() {
             grid.insert((i as i64, j as i64), ch);
         }
@@ -25,17 +113,19 @@ pub fn input_as_grid(day: i8) -> HashMap<(i64, i64), char> {
     grid
 }
 
-pub fn input_as_grids(day: i8) -> Vec<HashMap<(i64, i64), char>> {
-    let string = input_as_string(day);
-    let mut grids: Vec<HashMap<(i64, i64), char>> = Vec::new();
-    for grid_string in string.split("\n\n") {
-        let mut grid: HashMap<(i64, i64), char> = HashMap::new();
-        for (i, line) in grid_string.lines().enumerate() {
-            for (j, ch) in line.chars().enumerate() {
-                grid.insert((i as i64, j as i64), ch);
+// Grids are separated by one or more blank lines.
+pub fn grids(input: &str) -> Vec<Grid> {
+    let mut grids: Vec<Grid> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in input.lines().chain(std::iter::once("")) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                grids.push(grid(&current.join("\n")));
+                current.clear();
             }
+        } else {
+            current.push(line);
         }
-        grids.push(grid);
     }
     grids
 }
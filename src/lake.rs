@@ -1,18 +1,29 @@
-use crate::utils::Dir;
-use itertools::Itertools;
-use num::traits::FloatConst;
+use crate::answer::{checked_sum_opt, SolveError};
+use crate::assumption::Assumption;
+use crate::utils::{join, Dir};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Move {
     dir: Dir,
     dist: i64,
+    // The edge colour as 0xrrggbb, where the plan gives one.
+    colour: Option<u32>,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Path {
+    moves: Vec<Move>,
     points: HashSet<(i64, i64)>,
     vertices: Vec<(i64, i64)>,
 }
 
+// The trench is stored cell by cell, so keep it to something that fits in memory.
+const MAX_TRENCH: i64 = 1 << 20;
+
 fn convert(map: &mut HashMap<(i64, i64), usize>, from: usize, to: usize) {
     for v in map.values_mut() {
         if *v == from {
@@ -22,11 +33,14 @@ fn convert(map: &mut HashMap<(i64, i64), usize>, from: usize, to: usize) {
 }
 
 impl Path {
-    pub fn from_lines(lines: &Vec<String>) -> Option<Path> {
+    pub fn from_lines(lines: &[String]) -> Option<Path> {
         let moves = lines
             .iter()
             .map(|line| Move::from_line(line))
             .collect::<Option<Vec<Move>>>()?;
+        if moves.iter().map(|movement| movement.dist).sum::<i64>() > MAX_TRENCH {
+            return None;
+        }
         let mut points = HashSet::new();
         let mut vertices = Vec::new();
         let mut start: (i64, i64) = (0, 0);
@@ -41,7 +55,11 @@ impl Path {
             vertices.push(end);
             start = end;
         }
-        Some(Path { points, vertices })
+        Some(Path {
+            moves,
+            points,
+            vertices,
+        })
     }
 
     pub fn area(&self) -> usize {
@@ -55,6 +73,38 @@ impl Path {
         inner + self.points.len()
     }
 
+    pub fn shoelace_assumptions(&self) -> Vec<Assumption> {
+        vec![Assumption::new(
+            "the trench ends where it starts (shoelace)",
+            self.vertices.first() == self.vertices.last(),
+        )]
+    }
+
+    // Shoelace gives the area enclosed by the trench's centre line, and Pick's
+    // theorem turns that into the number of cells inside it. Fails with
+    // Assumption if the trench does not end where it started.
+    pub fn area_shoelace(&self) -> Result<usize, SolveError> {
+        if self.vertices.first() != self.vertices.last() {
+            return Err(SolveError::Assumption);
+        }
+        let twice_area: i64 = checked_sum_opt(self.vertices.windows(2).map(|w| {
+            w[0].0
+                .checked_mul(w[1].1)?
+                .checked_sub(w[1].0.checked_mul(w[0].1)?)
+        }))
+        .ok_or(SolveError::Overflow)?;
+        let boundary: i64 = checked_sum_opt(self.vertices.windows(2).map(|w| {
+            let across = w[1].0.checked_sub(w[0].0)?.checked_abs()?;
+            across.checked_add(w[1].1.checked_sub(w[0].1)?.checked_abs()?)
+        }))
+        .ok_or(SolveError::Overflow)?;
+        twice_area
+            .checked_abs()
+            .and_then(|twice_area| (twice_area / 2).checked_add(boundary / 2 + 1))
+            .and_then(|area| usize::try_from(area).ok())
+            .ok_or(SolveError::Overflow)
+    }
+
     fn connected_areas(&self) -> Vec<HashSet<(i64, i64)>> {
         let mut area_nums: HashSet<usize> = HashSet::new();
         let mut area_map: HashMap<(i64, i64), usize> = HashMap::new();
@@ -107,7 +157,6 @@ impl Path {
             }
             areas.push(area);
         }
-        println!("{} {:?} {:?}", areas.len(), max, min);
         areas
     }
 
@@ -134,13 +183,77 @@ fn dir_from_string(string: &str) -> Option<Dir> {
     }
 }
 
+fn dir_to_string(dir: Dir) -> &'static str {
+    match dir {
+        Dir::Right => "R",
+        Dir::Left => "L",
+        Dir::Up => "U",
+        Dir::Down => "D",
+    }
+}
+
+// The colour of an edge, written (#rrggbb).
+fn colour_from_string(string: &str) -> Option<u32> {
+    let hex = string.strip_prefix("(#")?.strip_suffix(')')?;
+    if hex.len() != 6 || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
 impl Move {
     fn from_line(line: &str) -> Option<Move> {
         let mut parts = line.split(" ");
         let dir_s = parts.next()?;
         let dir = dir_from_string(dir_s)?;
         let dist_s = parts.next()?;
-        let dist = dist_s.parse::<i64>().ok()?;
-        Some(Move { dir, dist })
+        let dist = dist_s
+            .parse::<i64>()
+            .ok()
+            .filter(|dist| (1..=MAX_TRENCH).contains(dist))?;
+        let colour = match parts.next() {
+            Some(colour_s) => Some(colour_from_string(colour_s)?),
+            None => None,
+        };
+        Some(Move { dir, dist, colour })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", dir_to_string(self.dir), self.dist)?;
+        if let Some(colour) = self.colour {
+            write!(f, " (#{:06x})", colour)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.moves, "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(text: &str) -> Path {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        Path::from_lines(&lines).unwrap()
+    }
+
+    #[test]
+    fn shoelace_counts_the_trench_and_inside() {
+        let square = path("R 2 (#000000)\nD 2 (#000000)\nL 2 (#000000)\nU 2 (#000000)");
+        assert_eq!(square.area_shoelace(), Ok(9));
+        assert_eq!(square.area(), 9);
+    }
+
+    #[test]
+    fn shoelace_needs_a_closed_trench() {
+        let open = path("R 2 (#000000)\nD 2 (#000000)");
+        assert_eq!(open.area_shoelace(), Err(SolveError::Assumption));
     }
 }
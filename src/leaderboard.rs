@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// The private leaderboard export, keeping only the fields the report uses.
+#[derive(Deserialize)]
+struct Board {
+    event: String,
+    members: HashMap<String, Member>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    id: u64,
+    name: Option<String>,
+    stars: u32,
+    local_score: u32,
+    #[serde(default)]
+    completion_day_level: HashMap<u8, HashMap<u8, Star>>,
+}
+
+#[derive(Deserialize)]
+struct Star {
+    get_star_ts: i64,
+}
+
+#[derive(Serialize)]
+pub struct MemberReport {
+    name: String,
+    stars: u32,
+    local_score: u32,
+    // Seconds between the two stars of each day.
+    part_two_delta: BTreeMap<u8, i64>,
+    // Local rank at the end of each puzzle day, starting with day 1.
+    ranks: Vec<usize>,
+}
+
+#[derive(Serialize)]
+pub struct DayReport {
+    day: u8,
+    fastest: Option<String>,
+    // Seconds from the puzzle unlocking to the fastest second star.
+    time: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    event: String,
+    members: Vec<MemberReport>,
+    days: Vec<DayReport>,
+}
+
+impl Member {
+    fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+
+    fn star(&self, day: u8, part: u8) -> Option<i64> {
+        Some(self.completion_day_level.get(&day)?.get(&part)?.get_star_ts)
+    }
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// Puzzles unlock at midnight EST, which is 05:00 UTC.
+fn unlock_ts(event: i64, day: u8) -> i64 {
+    days_from_civil(event, 12, day as i64) * 86400 + 5 * 3600
+}
+
+// Local scores award each star the number of members minus the number who
+// got it earlier. Only stars up to and including the given day count.
+fn scores_through(members: &[&Member], last_day: u8) -> Vec<u32> {
+    let mut scores = vec![0_u32; members.len()];
+    for day in 1..=last_day {
+        for part in 1..=2 {
+            let mut got: Vec<(i64, usize)> = members
+                .iter()
+                .enumerate()
+                .filter_map(|(i, member)| member.star(day, part).map(|ts| (ts, i)))
+                .collect();
+            got.sort();
+            for (place, (_, i)) in got.iter().enumerate() {
+                scores[*i] += (members.len() - place) as u32;
+            }
+        }
+    }
+    scores
+}
+
+fn ranks(scores: &[u32]) -> Vec<usize> {
+    scores
+        .iter()
+        .map(|score| 1 + scores.iter().filter(|other| *other > score).count())
+        .collect()
+}
+
+impl Report {
+    pub fn from_json(json: &str) -> Option<Report> {
+        let board: Board = serde_json::from_str(json).ok()?;
+        let event = board.event.parse::<i64>().ok()?;
+        let mut members: Vec<&Member> = board.members.values().collect();
+        members.sort_by_key(|member| (std::cmp::Reverse(member.local_score), member.id));
+        let last_day = members
+            .iter()
+            .flat_map(|member| member.completion_day_level.keys())
+            .max()
+            .copied()
+            .unwrap_or(0);
+        let daily_ranks: Vec<Vec<usize>> = (1..=last_day)
+            .map(|day| ranks(&scores_through(&members, day)))
+            .collect();
+        let member_reports = members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| MemberReport {
+                name: member.display_name(),
+                stars: member.stars,
+                local_score: member.local_score,
+                part_two_delta: (1..=last_day)
+                    .filter_map(|day| Some((day, member.star(day, 2)? - member.star(day, 1)?)))
+                    .collect(),
+                ranks: daily_ranks.iter().map(|ranks| ranks[i]).collect(),
+            })
+            .collect();
+        let days = (1..=last_day)
+            .map(|day| {
+                let fastest = members
+                    .iter()
+                    .filter_map(|member| member.star(day, 2).map(|ts| (ts, member)))
+                    .min_by_key(|(ts, _)| *ts);
+                DayReport {
+                    day,
+                    fastest: fastest.map(|(_, member)| member.display_name()),
+                    time: fastest.map(|(ts, _)| ts - unlock_ts(event, day)),
+                }
+            })
+            .collect();
+        Some(Report {
+            event: board.event,
+            members: member_reports,
+            days,
+        })
+    }
+
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string_pretty(self).ok()
+    }
+
+    pub fn to_table(&self) -> String {
+        let width = self
+            .members
+            .iter()
+            .map(|member| member.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(6);
+        let mut out = format!("Advent of Code {}\n\n", self.event);
+        out += &format!("{:<width$}  Stars  Score  Rank by day\n", "Member");
+        for member in &self.members {
+            let ranks = member
+                .ranks
+                .iter()
+                .map(|rank| rank.to_string())
+                .collect::<Vec<_>>();
+            out += &format!(
+                "{:<width$}  {:>5}  {:>5}  {}\n",
+                member.name,
+                member.stars,
+                member.local_score,
+                ranks.join(" ")
+            );
+        }
+        out += "\nPart 1 to part 2\n";
+        for day in &self.days {
+            let mut deltas: Vec<(i64, &str)> = self
+                .members
+                .iter()
+                .filter_map(|member| {
+                    Some((*member.part_two_delta.get(&day.day)?, member.name.as_str()))
+                })
+                .collect();
+            if deltas.is_empty() {
+                continue;
+            }
+            deltas.sort();
+            let deltas = deltas
+                .iter()
+                .map(|(delta, name)| format!("{} {}", name, duration(*delta)))
+                .collect::<Vec<_>>();
+            out += &format!("{:>3}  {}\n", day.day, deltas.join(", "));
+        }
+        out += "\nFastest per day\n";
+        for day in &self.days {
+            if let (Some(name), Some(time)) = (&day.fastest, day.time) {
+                out += &format!("{:>3}  {:<width$}  {}\n", day.day, name, duration(time));
+            }
+        }
+        out
+    }
+}
+
+fn duration(secs: i64) -> String {
+    let sign = if secs < 0 { "-" } else { "" };
+    let secs = secs.unsigned_abs();
+    format!(
+        "{}{}:{:02}:{:02}",
+        sign,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four members over two days. Cy and Dee tie on score, so Cy's lower ID
+    // lists them first and they share a rank.
+    const BOARD: &str = include_str!("../data/leaderboard.json");
+
+    #[test]
+    fn orders_members_by_score_then_id() {
+        let report = Report::from_json(BOARD).unwrap();
+        assert_eq!(report.event, "2023");
+        let names: Vec<&str> = report
+            .members
+            .iter()
+            .map(|member| member.name.as_str())
+            .collect();
+        assert_eq!(names, ["(anonymous user #1)", "Ada", "Cy", "Dee"]);
+        let stars: Vec<u32> = report.members.iter().map(|member| member.stars).collect();
+        assert_eq!(stars, [4, 3, 2, 2]);
+    }
+
+    #[test]
+    fn ranks_each_day_from_the_stars_so_far() {
+        let report = Report::from_json(BOARD).unwrap();
+        let daily: Vec<&[usize]> = report
+            .members
+            .iter()
+            .map(|member| &member.ranks[..])
+            .collect();
+        assert_eq!(daily, [&[2, 1], &[1, 2], &[4, 3], &[3, 3]]);
+        assert_eq!(ranks(&[5, 7, 5, 1]), [2, 1, 2, 4]);
+    }
+
+    #[test]
+    fn times_the_stars() {
+        let report = Report::from_json(BOARD).unwrap();
+        let deltas: Vec<Vec<(u8, i64)>> = report
+            .members
+            .iter()
+            .map(|member| member.part_two_delta.clone().into_iter().collect())
+            .collect();
+        assert_eq!(
+            deltas,
+            [vec![(1, 280), (2, 10)], vec![(1, 60)], vec![], vec![]]
+        );
+        let days: Vec<(u8, Option<&str>, Option<i64>)> = report
+            .days
+            .iter()
+            .map(|day| (day.day, day.fastest.as_deref(), day.time))
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (1, Some("Ada"), Some(160)),
+                (2, Some("(anonymous user #1)"), Some(60))
+            ]
+        );
+        assert_eq!(duration(3725), "1:02:05");
+        assert_eq!(duration(-5), "-0:00:05");
+    }
+
+    #[test]
+    fn rejects_a_board_it_cannot_read() {
+        assert!(Report::from_json("{").is_none());
+        assert!(Report::from_json(r#"{"event": "next", "members": {}}"#).is_none());
+        let empty = Report::from_json(r#"{"event": "2023", "members": {}}"#).unwrap();
+        assert!(empty.members.is_empty() && empty.days.is_empty());
+    }
+}
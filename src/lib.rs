@@ -0,0 +1,52 @@
+extern crate core;
+
+pub mod answer;
+pub mod assumption;
+pub mod beams;
+pub mod bricks;
+pub mod calibration;
+pub mod cards;
+pub mod cave;
+pub mod challenge;
+pub mod control;
+pub mod crucible;
+#[cfg(feature = "serde")]
+pub mod dump;
+pub mod examples;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod galaxy;
+#[cfg(feature = "serde")]
+mod grid_serde;
+pub mod hail;
+pub mod hash;
+#[cfg(feature = "serde")]
+pub mod history;
+pub mod io;
+pub mod lake;
+#[cfg(feature = "serde")]
+pub mod leaderboard;
+pub mod map;
+pub mod minimize;
+pub mod mirrors;
+pub mod parts;
+pub mod pipes;
+#[cfg(feature = "python")]
+mod python;
+pub mod race;
+pub mod render;
+pub mod scratchcards;
+pub mod seeds;
+pub mod sequences;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod signals;
+pub mod simulation;
+pub mod snow_game;
+pub mod steps;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod utils;
+pub mod walk;
+pub mod workflow;
+pub mod writer;
@@ -1,342 +1,975 @@
-#![feature(btree_cursors)]
-#![feature(ascii_char)]
-#![feature(inline_const)]
-extern crate core;
-
-mod beams;
-mod bricks;
-mod calibration;
-mod cards;
-mod cave;
-mod crucible;
-mod galaxy;
-mod hail;
-mod hash;
-mod io;
-mod lake;
-mod map;
-mod mirrors;
-mod parts;
-mod pipes;
-mod race;
-mod scratchcards;
-mod seeds;
-mod sequences;
-mod signals;
-mod snow_game;
-mod steps;
-mod utils;
-mod walk;
-mod workflow;
-
-mod challenge {
-    use super::*;
-
-    fn challenge_1() {
-        let data = io::input_as_lines(1);
-        println!("{:?}", calibration::calibration_total(&data, false));
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use advent_of_code::dump;
+#[cfg(feature = "serde")]
+use advent_of_code::history;
+#[cfg(feature = "serde")]
+use advent_of_code::leaderboard;
+#[cfg(feature = "serve")]
+use advent_of_code::serve;
+#[cfg(feature = "tui")]
+use advent_of_code::tui;
+use advent_of_code::{
+    answer, calibration, challenge, control, examples, io, minimize, simulation, snow_game, writer,
+};
+
+#[cfg(feature = "serde")]
+fn dump(args: &[String]) {
+    let day = args.first().and_then(|day| day.parse::<i8>().ok());
+    match (day, args.get(1).map(|s| s.as_str())) {
+        (Some(day), Some("--json")) => match dump::dump_json(day) {
+            Some(json) => println!("{}", json),
+            None => eprintln!("No parsed model for day {}", day),
+        },
+        _ => eprintln!("Usage: dump <day> --json"),
     }
+}
 
-    fn challenge_2() {
-        let data = io::input_as_lines(1);
-        println!("{:?}", calibration::calibration_total(&data, true));
-    }
+#[cfg(not(feature = "serde"))]
+fn dump(_args: &[String]) {
+    eprintln!("dump requires the serde feature");
+}
 
-    fn challenge_3() {
-        let data = io::input_as_lines(2);
-        let games = snow_game::Games::from_lines(&data).unwrap();
-        println!("{:?}", games.sum_possible());
+#[cfg(feature = "serde")]
+fn history(args: &[String]) {
+    let records = history::load();
+    match args {
+        [] => {
+            for record in &records {
+                println!(
+                    "{:>2}  {:<19}  {:>10.1}ms  {}",
+                    record.challenge,
+                    record.revision.as_deref().unwrap_or("-"),
+                    record.time_ms,
+                    record
+                        .answer
+                        .as_deref()
+                        .or(record.error.as_deref())
+                        .unwrap_or("")
+                );
+            }
+        }
+        [cmd, a, b] if cmd == "diff" => {
+            let diff = history::diff(&records, a, b);
+            for change in &diff.changed {
+                println!(
+                    "{:>2}  {:<12}  answer {} -> {}",
+                    change.challenge,
+                    change.implementation,
+                    change.before.as_deref().unwrap_or("-"),
+                    change.after.as_deref().unwrap_or("-")
+                );
+            }
+            for slowdown in &diff.slower {
+                println!(
+                    "{:>2}  {:<12}  slower {:.1}ms -> {:.1}ms",
+                    slowdown.challenge,
+                    slowdown.implementation,
+                    slowdown.before_ms,
+                    slowdown.after_ms
+                );
+            }
+            for (challenge, implementation) in &diff.added {
+                println!(
+                    "{:>2}  {:<12}  only run at {}",
+                    challenge, implementation, b
+                );
+            }
+            for (challenge, implementation) in &diff.removed {
+                println!(
+                    "{:>2}  {:<12}  only run at {}",
+                    challenge, implementation, a
+                );
+            }
+            if diff.changed.is_empty()
+                && diff.slower.is_empty()
+                && diff.added.is_empty()
+                && diff.removed.is_empty()
+            {
+                println!("No changes between {} and {}", a, b);
+            }
+        }
+        _ => eprintln!("Usage: history [diff <revision> <revision>]"),
     }
+}
 
-    fn challenge_4() {
-        let data = io::input_as_lines(2);
-        let games = snow_game::Games::from_lines(&data).unwrap();
-        println!("{:?}", games.sum_power_set());
-    }
+#[cfg(not(feature = "serde"))]
+fn history(_args: &[String]) {
+    eprintln!("history requires the serde feature");
+}
 
-    fn challenge_5() {
-        let data = io::input_as_lines(3);
-        let engine = parts::Engine::from_lines(&data);
-        println!("{:?}", engine.sum_part_numbers());
+#[cfg(feature = "serde")]
+fn record(
+    num: u8,
+    implementation: &str,
+    input: &str,
+    result: &Result<answer::Answer, answer::SolveError>,
+    elapsed: Duration,
+) {
+    let record = history::Record::new(num, implementation, input, result, elapsed);
+    if let Err(err) = history::append(&record) {
+        eprintln!("Could not record run: {}", err);
     }
+}
 
-    fn challenge_6() {
-        let data = io::input_as_lines(3);
-        let engine = parts::Engine::from_lines(&data);
-        println!("{:?}", engine.sum_gears());
-    }
+#[cfg(not(feature = "serde"))]
+fn record(
+    _num: u8,
+    _implementation: &str,
+    _input: &str,
+    _result: &Result<answer::Answer, answer::SolveError>,
+    _elapsed: Duration,
+) {
+    eprintln!("Run not recorded: history requires the serde feature");
+}
 
-    fn challenge_7() {
-        let data = io::input_as_lines(4);
-        let cards = scratchcards::ScratchCards::from_lines(&data).unwrap();
-        println!("{:?}", cards.winnings());
+#[cfg(feature = "serde")]
+fn leaderboard(args: &[String]) {
+    let as_json = args.get(1).map(|s| s.as_str()) == Some("--json");
+    let Some(file) = args.first().filter(|_| args.len() == 1 || as_json) else {
+        eprintln!("Usage: leaderboard <file.json> [--json]");
+        return;
+    };
+    let report = std::fs::read_to_string(file)
+        .map_err(|err| err.to_string())
+        .and_then(|json| {
+            leaderboard::Report::from_json(&json).ok_or("not a leaderboard export".to_string())
+        });
+    match report {
+        Ok(report) if as_json => println!("{}", report.to_json().unwrap_or_default()),
+        Ok(report) => print!("{}", report.to_table()),
+        Err(err) => eprintln!("Error: {}", err),
     }
+}
 
-    fn challenge_8() {
-        let data = io::input_as_lines(4);
-        let cards = scratchcards::ScratchCards::from_lines(&data).unwrap();
-        println!("{:?}", cards.winning_scratchcards());
-    }
+#[cfg(not(feature = "serde"))]
+fn leaderboard(_args: &[String]) {
+    eprintln!("leaderboard requires the serde feature");
+}
 
-    fn challenge_9() {
-        let data = io::input_as_string(5);
-        let almanac = seeds::Almanac::from_string(&data).unwrap();
-        println!("{:?}", almanac.least_site());
+#[cfg(feature = "serve")]
+fn serve(args: &[String]) {
+    let mut port: u16 = 8080;
+    let mut timeout = None;
+    let mut workers = std::thread::available_parallelism().map_or(4, |n| n.get());
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        let value = flags.next();
+        match (flag.as_str(), value) {
+            ("--port", Some(val)) if val.parse::<u16>().is_ok() => port = val.parse().unwrap(),
+            ("--timeout", Some(val)) if val.parse::<f64>().is_ok() => {
+                timeout = Some(Duration::from_secs_f64(val.parse().unwrap()))
+            }
+            ("--workers", Some(val)) if val.parse::<usize>().is_ok_and(|n| n > 0) => {
+                workers = val.parse().unwrap()
+            }
+            _ => {
+                eprintln!("Usage: serve [--port <port>] [--timeout <secs>] [--workers <count>]");
+                return;
+            }
+        }
     }
-
-    fn challenge_10() {
-        let data = io::input_as_string(5);
-        let almanac = seeds::Almanac::from_string(&data).unwrap();
-        println!("{:?}", almanac.least_site_ranges());
+    if let Err(err) = serve::serve(port, timeout, workers) {
+        eprintln!("Error: {}", err);
     }
+}
 
-    fn challenge_11() {
-        let data = io::input_as_lines(6);
-        let races = race::Races::from_lines(&data).unwrap();
-        println!("{:?}", races.score());
-    }
+#[cfg(not(feature = "serve"))]
+fn serve(_args: &[String]) {
+    eprintln!("serve requires the serve feature");
+}
 
-    fn challenge_12() {
-        let data = io::input_as_lines(6);
-        let race = race::Race::from_lines(&data).unwrap();
-        println!("{:?}", race.num_success());
+#[cfg(feature = "tui")]
+fn tui(_args: &[String]) {
+    if let Err(err) = tui::dashboard() {
+        eprintln!("Error: {}", err);
     }
+}
 
-    fn challenge_13() {
-        let data = io::input_as_lines(7);
-        let game = cards::Game::from_lines(&data, false).unwrap();
-        println!("{:?}", game.winnings());
-    }
+#[cfg(not(feature = "tui"))]
+fn tui(_args: &[String]) {
+    eprintln!("tui requires the tui feature");
+}
 
-    fn challenge_14() {
-        let data = io::input_as_lines(7);
-        let game = cards::Game::from_lines(&data, true).unwrap();
-        println!("{:?}", game.winnings());
+fn examples(args: &[String]) {
+    let day = args.get(1).and_then(|day| day.parse::<u8>().ok());
+    match (args.first().map(|s| s.as_str()), day, args.get(2)) {
+        (Some("import"), Some(day), Some(file)) => match examples::import(day, file) {
+            Ok(written) => {
+                for file in written {
+                    println!("wrote {}", file);
+                }
+            }
+            Err(err) => eprintln!("Error: {}", err),
+        },
+        (Some("check"), Some(day), None) => {
+            let outcomes = examples::check(day, &control::Control::new());
+            if outcomes.is_empty() {
+                eprintln!("No examples imported for day {}", day);
+            }
+            for (part, outcome) in outcomes {
+                match outcome {
+                    examples::Outcome::Pass => println!("day {} part {}: ok", day, part),
+                    examples::Outcome::Fail(got) => {
+                        println!("day {} part {}: got {}", day, part, got)
+                    }
+                    examples::Outcome::Error(err) => {
+                        println!("day {} part {}: {}", day, part, err)
+                    }
+                }
+            }
+        }
+        _ => eprintln!("Usage: examples import <day> <file.html> | examples check <day>"),
     }
+}
 
-    fn challenge_15() {
-        let data = io::input_as_string(8);
-        let map = map::Map::from_string(&data).unwrap();
-        println!("{:?}", map.num_steps());
+const RUN_USAGE: &str =
+    "Usage: <challenge> [--timeout <secs>] [--progress] [--strict] [--impl <name>]";
+
+fn run(num: u8, args: &[String]) {
+    let mut control = control::Control::new();
+    let mut strict = false;
+    let mut implementation = None;
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--timeout" => {
+                let secs = flags.next().and_then(|s| s.parse::<f64>().ok());
+                match secs {
+                    Some(secs) => control = control.with_timeout(Duration::from_secs_f64(secs)),
+                    None => {
+                        eprintln!("{}", RUN_USAGE);
+                        return;
+                    }
+                }
+            }
+            "--impl" => match flags.next() {
+                Some(name) => implementation = Some(name.as_str()),
+                None => {
+                    eprintln!("{}", RUN_USAGE);
+                    return;
+                }
+            },
+            "--progress" => control = control.with_progress(control::progress_bar),
+            "--strict" => strict = true,
+            _ => {
+                eprintln!("Unknown flag {}", flag);
+                return;
+            }
+        }
     }
-
-    fn challenge_16() {
-        let data = io::input_as_string(8);
-        let map = map::Map::from_string(&data).unwrap();
-        println!("{:?}", map.num_steps_ghost());
+    let implementations = challenge::implementations(num);
+    let implementation = implementation.unwrap_or(implementations[0]);
+    if !implementations.contains(&implementation) {
+        eprintln!(
+            "No implementation {} for challenge {}, choose from: {}",
+            implementation,
+            num,
+            implementations.join(", ")
+        );
+        return;
+    }
+    let cancelled = control.cancel_flag();
+    // Let Ctrl-C stop the solver at its next tick instead of killing the process.
+    let _ = ctrlc::set_handler(move || cancelled.store(true, Ordering::Relaxed));
+    let input = match io::read_input(challenge::day(num), strict) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    let start = Instant::now();
+    let result = challenge::challenge_impl(num, implementation, &input, &control);
+    record(num, implementation, &input, &result, start.elapsed());
+    if control.has_progress() {
+        eprintln!();
     }
-
-    fn challenge_17() {
-        let data = io::input_as_lines(9);
-        let gens = sequences::SequenceGens::from_lines(&data).unwrap();
-        println!("{:?}", gens.next_sum());
+    match result {
+        Ok(answer) => println!("{}", answer),
+        Err(err) => eprintln!("Error: {}", err),
     }
+}
 
-    fn challenge_18() {
-        let data = io::input_as_lines(9);
-        let gens = sequences::SequenceGens::from_lines(&data).unwrap();
-        println!("{:?}", gens.prev_sum());
+fn compare(args: &[String]) {
+    let day = args.first().and_then(|day| day.parse::<u8>().ok());
+    let timeout = match args.get(1..) {
+        Some([flag, secs]) if flag == "--timeout" => secs.parse::<f64>().ok(),
+        _ => None,
+    };
+    let (Some(day), true) = (day, args.len() == 1 || timeout.is_some()) else {
+        eprintln!("Usage: compare <day> [--timeout <secs>]");
+        return;
+    };
+    let input = match io::read_input(day as i8, false) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    let mut disagree = false;
+    for part in 1..=2 {
+        let Some(num) = challenge::number(day, part) else {
+            continue;
+        };
+        let mut answers = Vec::new();
+        for name in challenge::implementations(num) {
+            let mut control = control::Control::new();
+            if let Some(secs) = timeout {
+                control = control.with_timeout(Duration::from_secs_f64(secs));
+            }
+            let start = Instant::now();
+            let result = challenge::challenge_impl(num, name, &input, &control);
+            let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+            let shown = match &result {
+                Ok(answer) => answer.to_string(),
+                Err(err) => format!("error: {}", err),
+            };
+            println!(
+                "day {} part {}  {:<12} {:>10.1}ms  {}",
+                day, part, name, elapsed, shown
+            );
+            if let Ok(answer) = result {
+                answers.push(answer);
+            }
+        }
+        // Failed runs, such as ones that timed out, have nothing to agree with.
+        if answers.windows(2).any(|pair| pair[0] != pair[1]) {
+            println!("day {} part {}  implementations disagree", day, part);
+            disagree = true;
+        }
     }
-
-    fn challenge_19() {
-        let data = io::input_as_grid(10);
-        let pipes = pipes::Pipes::from_grid(&data);
-        println!("{:?}", pipes.max_distance());
+    if disagree {
+        std::process::exit(1);
     }
+}
 
-    fn challenge_20() {
-        let data = io::input_as_grid(0);
-        let pipes = pipes::Pipes::from_grid(&data);
-        println!("{:?}", pipes.enclosed());
+fn check(args: &[String]) {
+    let day = args.first().and_then(|day| day.parse::<u8>().ok());
+    let (Some(day), 1..=2) = (day, args.len()) else {
+        eprintln!("Usage: check <day> [input file]");
+        return;
+    };
+    let input = match args.get(1) {
+        Some(file) => std::fs::read_to_string(file).map_err(|err| err.to_string()),
+        None => io::read_input(day as i8, false).map_err(|err| err.to_string()),
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    let mut broken = false;
+    for part in 1..=2 {
+        let Some(num) = challenge::number(day, part) else {
+            continue;
+        };
+        match challenge::assumptions(num, &input) {
+            Ok(assumptions) => {
+                for assumption in assumptions {
+                    let status = if assumption.holds { "holds" } else { "FAILS" };
+                    println!(
+                        "day {} part {}  {}  {}",
+                        day, part, status, assumption.description
+                    );
+                    broken |= !assumption.holds;
+                }
+            }
+            Err(err) => println!("day {} part {}  {}", day, part, err),
+        }
     }
-
-    fn challenge_21() {
-        let data = io::input_as_grid(11);
-        let galaxy = galaxy::Galaxy::from_grid(2, &data);
-        println!("{:?}", galaxy.sum_dist());
+    if broken {
+        std::process::exit(1);
     }
+}
 
-    fn challenge_22() {
-        let data = io::input_as_grid(11);
-        let galaxy = galaxy::Galaxy::from_grid(1000000, &data);
-        println!("{:?}", galaxy.sum_dist());
+fn write(args: &[String]) {
+    let check = args.last().map(|s| s.as_str()) == Some("--check");
+    let args = &args[..args.len() - check as usize];
+    let day = args.first().and_then(|day| day.parse::<u8>().ok());
+    let (Some(day), 1..=2) = (day, args.len()) else {
+        eprintln!("Usage: write <day> [input file] [--check]");
+        return;
+    };
+    let input = match args.get(1) {
+        Some(file) => std::fs::read_to_string(file).map_err(|err| err.to_string()),
+        None => io::read_input(day as i8, false).map_err(|err| err.to_string()),
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    if !check {
+        match writer::write(day, &input) {
+            Ok(text) => println!("{}", text),
+            Err(err) => eprintln!("Error: {}", err),
+        }
+        return;
     }
-
-    fn challenge_25() {
-        let data = io::input_as_grids(13);
-        let caves = cave::Caves::from_grids(&data);
-        println!("{:?}", caves.score());
+    match (writer::round_trips(day, &input), writer::write(day, &input)) {
+        (Ok(true), Ok(text)) if text == io::normalize(&input) => {
+            println!("day {} round trips and matches the input", day)
+        }
+        (Ok(true), _) => println!("day {} round trips in canonical form", day),
+        (Ok(false), _) => {
+            println!("day {} does not round trip", day);
+            std::process::exit(1);
+        }
+        (Err(err), _) => eprintln!("Error: {}", err),
     }
+}
 
-    fn challenge_26() {
-        let data = io::input_as_grids(13);
-        let caves = cave::Caves::from_grids(&data);
-        println!("{:?}", caves.score_smudge());
-    }
+const BAGS_USAGE: &str =
+    "Usage: bags [input file] [--bag <limits, e.g. \"12 red, 13 green, 14 blue\">]... \
+    [--unknown <error|ignore|zero>] [--estimate [--prior <mean cubes per colour>] \
+    [--max <total cubes, at most 1000>] [--timeout <secs, default 60>]]";
+
+// The cube games in the file, or in the day 2 input.
+fn cube_games(file: Option<&String>) -> Result<snow_game::Games, String> {
+    let input = match file {
+        Some(file) => std::fs::read_to_string(file).map_err(|err| err.to_string())?,
+        None => io::read_input(2, false).map_err(|err| err.to_string())?,
+    };
+    snow_game::Games::from_lines(&io::lines(&io::normalize(&input)))
+        .ok_or(answer::SolveError::Parse.to_string())
+}
 
-    fn challenge_27() {
-        let data = io::input_as_grid(14);
-        let mut beam = beams::Beam::from_grid(&data);
-        println!("{:?}", beam.score_north());
-    }
+fn ids(ids: &[u32]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    ids.join(", ")
+}
 
-    fn challenge_28() {
-        let data = io::input_as_grid(14);
-        let mut beam = beams::Beam::from_grid(&data);
-        println!("{:?}", beam.score_spin(1000000000));
+// Which bags the cube games could have been played with.
+fn bags(args: &[String]) {
+    let mut file = None;
+    let mut candidates = Vec::new();
+    let mut unknown = snow_game::UnknownColour::Error;
+    let mut estimate = false;
+    let mut prior_mean = 12.0;
+    let mut max_cubes = 1000;
+    let mut timeout = Duration::from_secs(60);
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--bag" => match flags
+                .next()
+                .and_then(|bag| snow_game::Bag::from_string(bag))
+            {
+                Some(bag) => candidates.push(bag),
+                None => {
+                    eprintln!("{}", BAGS_USAGE);
+                    return;
+                }
+            },
+            "--unknown" => match flags
+                .next()
+                .and_then(|s| snow_game::UnknownColour::from_string(s))
+            {
+                Some(policy) => unknown = policy,
+                None => {
+                    eprintln!("{}", BAGS_USAGE);
+                    return;
+                }
+            },
+            "--estimate" => estimate = true,
+            "--prior" => match flags.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(mean) if mean > 0.0 && mean.is_finite() => prior_mean = mean,
+                _ => {
+                    eprintln!("{}", BAGS_USAGE);
+                    return;
+                }
+            },
+            "--max" => match flags.next().and_then(|s| s.parse::<u32>().ok()) {
+                Some(max) if max <= snow_game::MAX_CUBES => max_cubes = max,
+                _ => {
+                    eprintln!("{}", BAGS_USAGE);
+                    return;
+                }
+            },
+            "--timeout" => match flags.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(secs) if secs >= 0.0 && secs.is_finite() => {
+                    timeout = Duration::from_secs_f64(secs)
+                }
+                _ => {
+                    eprintln!("{}", BAGS_USAGE);
+                    return;
+                }
+            },
+            _ if file.is_none() && !flag.starts_with("--") => file = Some(flag),
+            _ => {
+                eprintln!("{}", BAGS_USAGE);
+                return;
+            }
+        }
     }
-
-    fn challenge_29() {
-        let data = io::input_as_string(15);
-        let commands = hash::Commands::from_line(&data);
-        println!("{:?}", commands.hash_sum());
+    if candidates.is_empty() {
+        candidates.push(snow_game::Bag::puzzle());
     }
-
-    fn challenge_30() {
-        let data = io::input_as_string(15);
-        let boxes = hash::Boxes::from_line(&data);
-        println!("{:?}", boxes.focus_power_sum());
+    let games = match cube_games(file) {
+        Ok(games) => games,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    let minimal = games.minimal_bag();
+    println!(
+        "Smallest bag for every game: {} ({} cubes)",
+        minimal,
+        minimal.total().unwrap_or(u64::MAX)
+    );
+    println!("Binding games:");
+    for (colour, (limit, binding)) in games.binding_games() {
+        println!("  {:>3} {:<8} games {}", limit, colour, ids(&binding));
+    }
+    let candidates: Vec<(snow_game::Bag, Option<Vec<u32>>)> = candidates
+        .into_iter()
+        .map(|bag| {
+            let bag = bag.with_unknown(unknown);
+            let possible = games.possible_games(&bag);
+            (bag, possible)
+        })
+        .collect();
+    for (bag, possible) in &candidates {
+        match possible {
+            Some(possible) => println!(
+                "Bag {}: {} of {} games possible: {}",
+                bag,
+                possible.len(),
+                games.len(),
+                ids(possible)
+            ),
+            None => println!(
+                "Bag {}: a game shows a colour the bag has no limit for",
+                bag
+            ),
+        }
     }
-
-    fn challenge_31() {
-        let data = io::input_as_grid(16);
-        let cave = mirrors::MirrorCave::from_grid(&data);
-        println!("{:?}", cave.simulate_excited());
+    if estimate {
+        estimate_bags(&games, &candidates, prior_mean, max_cubes, timeout);
     }
+}
 
-    fn challenge_32() {
-        let data = io::input_as_grid(16);
-        let cave = mirrors::MirrorCave::from_grid(&data);
-        println!("{:?}", cave.max_simulate_excited());
+fn estimate_bags(
+    games: &snow_game::Games,
+    candidates: &[(snow_game::Bag, Option<Vec<u32>>)],
+    prior_mean: f64,
+    max_cubes: u32,
+    timeout: Duration,
+) {
+    let control = control::Control::new().with_timeout(timeout);
+    let cancelled = control.cancel_flag();
+    let _ = ctrlc::set_handler(move || cancelled.store(true, Ordering::Relaxed));
+    let estimates = match games.estimate(prior_mean, max_cubes, &control) {
+        Ok(estimates) => estimates,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    println!(
+        "Most probable bags given {} cubes of each colour expected beforehand, \
+        with 90% credible ranges:",
+        prior_mean
+    );
+    for (id, estimate) in estimates {
+        let Some(estimate) = estimate else {
+            println!("Game {:>3}: needs more than {} cubes", id, max_cubes);
+            continue;
+        };
+        let ranges: Vec<String> = estimate
+            .ranges
+            .iter()
+            .map(|(colour, (low, high))| format!("{} {}-{}", colour, low, high))
+            .collect();
+        println!("Game {:>3}: {}  ({})", id, estimate.bag, ranges.join(", "));
+        if estimate.bounded {
+            println!(
+                "          cut off at {} cubes, so larger bags were left out",
+                max_cubes
+            );
+        }
+        for (bag, possible) in candidates {
+            let verdict = match possible {
+                Some(possible) if possible.contains(&id) => "possible",
+                Some(_) => "impossible",
+                None => "unknown colour",
+            };
+            let relative = games
+                .log_likelihood(id, bag)
+                .map(|log_likelihood| (log_likelihood - estimate.log_likelihood).exp());
+            match relative {
+                Some(relative) => println!(
+                    "          {}: {}, {:.2e} times as likely",
+                    bag, verdict, relative
+                ),
+                None => println!("          {}: {}", bag, verdict),
+            }
+        }
     }
+}
 
-    fn challenge_33() {
-        let data = io::input_as_grid(17);
-        let city = crucible::City::from_grid(&data);
-        println!("{:?}", city.least_path(1, 3));
+const GAMES_USAGE: &str = "Usage: games [input file] [--bag <limits>] \
+    [--unknown <error|ignore|zero>] [--json]";
+
+// Every cube game checked against a bag, in the order they are listed.
+fn games(args: &[String]) {
+    let mut file = None;
+    let mut bag = snow_game::Bag::puzzle();
+    let mut unknown = snow_game::UnknownColour::Error;
+    let mut json = false;
+    let mut flags = args.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--bag" => match flags
+                .next()
+                .and_then(|bag| snow_game::Bag::from_string(bag))
+            {
+                Some(limits) => bag = limits,
+                None => {
+                    eprintln!("{}", GAMES_USAGE);
+                    return;
+                }
+            },
+            "--unknown" => match flags
+                .next()
+                .and_then(|s| snow_game::UnknownColour::from_string(s))
+            {
+                Some(policy) => unknown = policy,
+                None => {
+                    eprintln!("{}", GAMES_USAGE);
+                    return;
+                }
+            },
+            "--json" => json = true,
+            _ if file.is_none() && !flag.starts_with("--") => file = Some(flag),
+            _ => {
+                eprintln!("{}", GAMES_USAGE);
+                return;
+            }
+        }
     }
-
-    fn challenge_34() {
-        let data = io::input_as_grid(17);
-        let city = crucible::City::from_grid(&data);
-        println!("{:?}", city.least_path(4, 10));
+    let games = match cube_games(file) {
+        Ok(games) => games,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    let bag = bag.with_unknown(unknown);
+    let Some(reports) = games.report(&bag) else {
+        eprintln!(
+            "Error: a game shows a colour the bag {} has no limit for",
+            bag
+        );
+        return;
+    };
+    if json {
+        games_json(&reports);
+        return;
+    }
+    let minimal: Vec<String> = reports
+        .iter()
+        .map(|report| report.minimal.to_string())
+        .collect();
+    let width = minimal
+        .iter()
+        .map(|bag| bag.len())
+        .max()
+        .unwrap_or(0)
+        .max(11);
+    println!(
+        "{:>5}  {:>6}  {:<width$}  {:>8}  Against {}",
+        "Game", "Rounds", "Minimal bag", "Power", bag
+    );
+    for (report, minimal) in reports.iter().zip(&minimal) {
+        let power = report
+            .power
+            .map_or("overflow".to_string(), |power| power.to_string());
+        let verdict = match &report.violation {
+            None => "possible".to_string(),
+            Some(violation) => format!(
+                "impossible: round {}, {} {} > {}",
+                violation.round, violation.count, violation.colour, violation.limit
+            ),
+        };
+        println!(
+            "{:>5}  {:>6}  {:<width$}  {:>8}  {}",
+            report.id, report.rounds, minimal, power, verdict
+        );
     }
+}
 
-    fn challenge_35() {
-        let data = io::input_as_lines(18);
-        let path = lake::Path::from_lines(&data).unwrap();
-        println!("{:?}", path.area());
+#[cfg(feature = "serde")]
+fn games_json(reports: &[snow_game::GameReport]) {
+    match serde_json::to_string_pretty(reports) {
+        Ok(json) => println!("{}", json),
+        Err(err) => eprintln!("Error: {}", err),
     }
+}
 
-    fn challenge_37() {
-        let data = io::input_as_string(19);
-        let pile = workflow::Pile::from_string(&data).unwrap();
-        println!("{:?}", pile.accepted_ratings());
-    }
+#[cfg(not(feature = "serde"))]
+fn games_json(_reports: &[snow_game::GameReport]) {
+    eprintln!("games --json requires the serde feature");
+}
 
-    fn challenge_38() {
-        let data = io::input_as_string(19);
-        let pile = workflow::Pile::from_string(&data).unwrap();
-        println!("{:?}", pile.accepted_combos());
-    }
+const CALIBRATE_USAGE: &str =
+    "Usage: calibrate <file or -> [--lexicon <digits|english|german|french|file>] \
+    [--chunk <lines> | --explain]";
 
-    fn challenge_39() {
-        let data = io::input_as_lines(20);
-        let mut network = signals::Network::from_lines(&data).unwrap();
-        println!("{:?}", network.count_signals(1000));
+// A built in lexicon by name, or one loaded from a file.
+fn lexicon(name: &str) -> Result<calibration::Lexicon, String> {
+    if let Some(lexicon) = calibration::Lexicon::by_name(name) {
+        return Ok(lexicon);
     }
+    let text = std::fs::read_to_string(name).map_err(|err| format!("{}: {}", name, err))?;
+    calibration::Lexicon::from_string(&text).ok_or(format!("{} is not a lexicon", name))
+}
 
-    fn challenge_40() {
-        let data = io::input_as_lines(20);
-        let mut network = signals::Network::from_lines(&data).unwrap();
-        println!("{:?}", network.count_presses());
+fn calibrate(args: &[String]) {
+    let Some(file) = args.first() else {
+        eprintln!("{}", CALIBRATE_USAGE);
+        return;
+    };
+    let mut words = calibration::Lexicon::digits();
+    let mut chunk_lines = None;
+    let mut explain = false;
+    let mut flags = args[1..].iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--lexicon" => match flags.next().map(|name| lexicon(name)) {
+                Some(Ok(lexicon)) => words = lexicon,
+                Some(Err(err)) => {
+                    eprintln!("Error: {}", err);
+                    return;
+                }
+                None => {
+                    eprintln!("{}", CALIBRATE_USAGE);
+                    return;
+                }
+            },
+            "--explain" => explain = true,
+            "--chunk" => match flags.next().and_then(|s| s.parse::<usize>().ok()) {
+                Some(lines) if lines > 0 => chunk_lines = Some(lines),
+                _ => {
+                    eprintln!("{}", CALIBRATE_USAGE);
+                    return;
+                }
+            },
+            _ => {
+                eprintln!("{}", CALIBRATE_USAGE);
+                return;
+            }
+        }
     }
-
-    fn challenge_41() {
-        let data = io::input_as_grid(21);
-        let map = steps::Map::from_grid(&data);
-        println!("{:?}", map.steps(64));
+    if explain {
+        let text = if file == "-" {
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            std::fs::read_to_string(file)
+        };
+        match text {
+            Ok(text) => explain_calibration(&io::lines(&text), &words),
+            Err(err) => eprintln!("Error: {}", err),
+        }
+        return;
     }
-
-    fn challenge_43() {
-        let data = io::input_as_lines(22);
-        let mut bricks = bricks::Bricks::from_lines(&data).unwrap();
-        println!("{:?}", bricks.disintergrate_count());
+    let report = |chunk: calibration::Subtotal| {
+        println!(
+            "lines {}-{}: {}",
+            chunk.first_line,
+            chunk.first_line + chunk.lines - 1,
+            chunk.total
+        )
+    };
+    let total = if file == "-" {
+        calibration::calibration_stream(std::io::stdin().lock(), &words, chunk_lines, report)
+    } else {
+        std::fs::File::open(file).and_then(|file| {
+            let reader = std::io::BufReader::new(file);
+            calibration::calibration_stream(reader, &words, chunk_lines, report)
+        })
+    };
+    match total {
+        Ok(Some(total)) => println!("{}", total),
+        Ok(None) => eprintln!("Error: {}", answer::SolveError::Overflow),
+        Err(err) => eprintln!("Error: {}", err),
     }
+}
 
-    fn challenge_44() {
-        let data = io::input_as_lines(22);
-        let mut bricks = bricks::Bricks::from_lines(&data).unwrap();
-        println!("{:?}", bricks.maximum_chain());
+fn explain_calibration(lines: &[String], lexicon: &calibration::Lexicon) {
+    let shown = |found: &Option<calibration::Match>| match found {
+        Some(found) => format!(
+            "{:?} {}..{} {}",
+            found.text,
+            found.start,
+            found.end,
+            if found.word { "word" } else { "digit" }
+        ),
+        None => "-".to_string(),
+    };
+    let (mut no_match, mut differs) = (0, 0);
+    for report in calibration::explain(lines, lexicon) {
+        let mut flags = Vec::new();
+        if report.no_match() {
+            flags.push("no match".to_string());
+            no_match += 1;
+        }
+        if report.differs() {
+            flags.push(format!("digits alone give {}", report.digit_value));
+            differs += 1;
+        }
+        println!(
+            "{:>6}  {:>2}  first {:<22} last {:<22} {}",
+            report.line,
+            report.value,
+            shown(&report.first),
+            shown(&report.last),
+            flags.join(", ")
+        );
     }
+    println!(
+        "{} lines, {} with no match, {} differing from digits alone",
+        lines.len(),
+        no_match,
+        differs
+    );
+}
 
-    fn challenge_45() {
-        let data = io::input_as_grid(23);
-        let woods = walk::Woods::from_grid(&data);
-        println!("{:?}", woods.maximum_path(false));
+const MINIMIZE_USAGE: &str = "Usage: minimize <day> <input file> --predicate \
+    <panic|error|disagree|cmd=<command>> [--part <1|2>] [--timeout <secs>]";
+
+fn minimize(args: &[String]) {
+    let day = args.first().and_then(|day| day.parse::<u8>().ok());
+    let (Some(day), Some(file)) = (day, args.get(1)) else {
+        eprintln!("{}", MINIMIZE_USAGE);
+        return;
+    };
+    let mut predicate = None;
+    let mut part = 1;
+    let mut timeout = Duration::from_secs(10);
+    let mut flags = args[2..].iter();
+    while let Some(flag) = flags.next() {
+        let value = flags.next();
+        match (flag.as_str(), value) {
+            ("--predicate", Some(val)) if minimize::Predicate::from_string(val).is_some() => {
+                predicate = minimize::Predicate::from_string(val)
+            }
+            ("--part", Some(val)) if val == "1" || val == "2" => part = val.parse().unwrap(),
+            ("--timeout", Some(val)) if val.parse::<f64>().is_ok() => {
+                timeout = Duration::from_secs_f64(val.parse().unwrap())
+            }
+            _ => {
+                eprintln!("{}", MINIMIZE_USAGE);
+                return;
+            }
+        }
     }
-
-    fn challenge_46() {
-        let data = io::input_as_grid(23);
-        let woods = walk::Woods::from_grid(&data);
-        println!("{:?}", woods.maximum_path(true));
+    let Some(predicate) = predicate else {
+        eprintln!("{}", MINIMIZE_USAGE);
+        return;
+    };
+    let num = challenge::number(day, part).unwrap_or(0);
+    if matches!(predicate, minimize::Predicate::Disagrees)
+        && challenge::implementations(num).len() < 2
+    {
+        eprintln!(
+            "Error: day {} part {} has only one implementation",
+            day, part
+        );
+        return;
+    }
+    let input = match std::fs::read_to_string(file) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    match minimize::minimize(day, part, &input, &predicate, timeout) {
+        Some(minimized) => println!("{}", minimized),
+        None => {
+            eprintln!("The predicate does not hold for {}", file);
+            std::process::exit(1);
+        }
     }
+}
 
-    fn challenge_47() {
-        let data = io::input_as_lines(24);
-        let storm = hail::Storm::from_lines(&data).unwrap();
-        println!(
-            "{:?}",
-            storm.intersect_in_area(200000000000000, 400000000000000 + 1)
-        );
+const DEBUG_HELP: &str = "n [count] step forward, b [count] rewind, j <tick> jump, p print, q quit";
+
+fn debug(args: &[String]) {
+    let day = args.first().and_then(|day| day.parse::<u8>().ok());
+    let (Some(day), 1..=2) = (day, args.len()) else {
+        eprintln!("Usage: debug <day> [input file]");
+        return;
+    };
+    let input = match args.get(1) {
+        Some(file) => std::fs::read_to_string(file).map_err(|err| err.to_string()),
+        None => io::read_input(day as i8, false).map_err(|err| err.to_string()),
+    };
+    let session = input.and_then(|input| {
+        simulation::with_debugger(day, &input, debug_session).map_err(|err| err.to_string())
+    });
+    if let Err(err) = session {
+        eprintln!("Error: {}", err);
     }
+}
 
-    pub fn challenge(num: u8) {
-        match num {
-            1 => challenge_1(),
-            2 => challenge_2(),
-            3 => challenge_3(),
-            4 => challenge_4(),
-            5 => challenge_5(),
-            6 => challenge_6(),
-            7 => challenge_7(),
-            8 => challenge_8(),
-            9 => challenge_9(),
-            10 => challenge_10(),
-            11 => challenge_11(),
-            12 => challenge_12(),
-            13 => challenge_13(),
-            14 => challenge_14(),
-            15 => challenge_15(),
-            16 => challenge_16(),
-            17 => challenge_17(),
-            18 => challenge_18(),
-            19 => challenge_19(),
-            20 => challenge_20(),
-            21 => challenge_21(),
-            22 => challenge_22(),
-            25 => challenge_25(),
-            26 => challenge_26(),
-            27 => challenge_27(),
-            28 => challenge_28(),
-            29 => challenge_29(),
-            30 => challenge_30(),
-            31 => challenge_31(),
-            32 => challenge_32(),
-            33 => challenge_33(),
-            34 => challenge_34(),
-            35 => challenge_35(),
-            37 => challenge_37(),
-            38 => challenge_38(),
-            39 => challenge_39(),
-            40 => challenge_40(),
-            41 => challenge_41(),
-            43 => challenge_43(),
-            44 => challenge_44(),
-            45 => challenge_45(),
-            46 => challenge_46(),
-            47 => challenge_47(),
-            _ => (),
+// Steps the debugger as told on stdin until it runs out or is told to quit.
+fn debug_session(debugger: &mut dyn simulation::Stepper) {
+    println!("{}", debugger.state());
+    println!("{}", DEBUG_HELP);
+    for line in std::io::stdin().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("n");
+        let count = match words.next().map(|count| count.parse::<usize>()) {
+            None => None,
+            Some(Ok(count)) => Some(count),
+            Some(Err(_)) => {
+                println!("{}", DEBUG_HELP);
+                continue;
+            }
+        };
+        let before = debugger.state();
+        match (command, count) {
+            ("n", count) => {
+                for _ in 0..count.unwrap_or(1) {
+                    if !debugger.forward() {
+                        println!("finished at tick {}", debugger.tick());
+                        break;
+                    }
+                }
+            }
+            ("b", count) => {
+                for _ in 0..count.unwrap_or(1) {
+                    debugger.back();
+                }
+            }
+            ("j", Some(tick)) => debugger.jump(tick),
+            ("p", None) => {
+                println!("{}", debugger.state());
+                continue;
+            }
+            ("q", None) => break,
+            _ => {
+                println!("{}", DEBUG_HELP);
+                continue;
+            }
+        }
+        println!("tick {}", debugger.tick());
+        for change in simulation::changes(&before, &debugger.state()) {
+            println!("{}", change);
         }
     }
 }
@@ -344,6 +977,22 @@ mod challenge {
 fn main() {
     let default = "47".to_string();
     let args: Vec<String> = std::env::args().collect();
-    let ver = args.get(1).unwrap_or(&default).parse::<u8>().unwrap();
-    challenge::challenge(ver);
+    let command = args.get(1).unwrap_or(&default);
+    match command.as_str() {
+        "bags" => bags(&args[2..]),
+        "calibrate" => calibrate(&args[2..]),
+        "check" => check(&args[2..]),
+        "compare" => compare(&args[2..]),
+        "debug" => debug(&args[2..]),
+        "dump" => dump(&args[2..]),
+        "examples" => examples(&args[2..]),
+        "games" => games(&args[2..]),
+        "history" => history(&args[2..]),
+        "leaderboard" => leaderboard(&args[2..]),
+        "minimize" => minimize(&args[2..]),
+        "serve" => serve(&args[2..]),
+        "write" => write(&args[2..]),
+        "tui" => tui(&args[2..]),
+        _ => run(command.parse::<u8>().unwrap(), args.get(2..).unwrap_or(&[])),
+    }
 }
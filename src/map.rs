@@ -1,6 +1,10 @@
+use crate::assumption::Assumption;
 use num::Integer;
 use std::collections::HashMap;
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Dir {
     R,
     L,
@@ -16,6 +20,8 @@ impl Dir {
     }
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Map {
     directions: Vec<Dir>,
     map: HashMap<String, (String, String)>,
@@ -28,33 +34,52 @@ fn split_line(line: &str) -> Option<(String, String, String)> {
     Some((source.to_string(), left.to_string(), right.to_string()))
 }
 
+fn checked_lcm(a: usize, b: usize) -> Option<usize> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / a.gcd(&b)).checked_mul(b)
+}
+
 impl Map {
-    pub fn from_string(string: &String) -> Option<Map> {
+    pub fn from_string(string: &str) -> Option<Map> {
         let (dir_str, map_str) = string.split_once("\n\n")?;
         let directions = dir_str
             .chars()
-            .map(|ch| Dir::from_char(ch))
+            .map(Dir::from_char)
             .collect::<Option<Vec<Dir>>>()?;
         let mut map: HashMap<String, (String, String)> = HashMap::new();
         for line in map_str.lines() {
             let (source, left, right) = split_line(line)?;
             map.insert(source, (left, right));
         }
+        // Every node has to lead somewhere we know about.
+        if directions.is_empty()
+            || map
+                .values()
+                .any(|(left, right)| !map.contains_key(left) || !map.contains_key(right))
+        {
+            return None;
+        }
         Some(Map { directions, map })
     }
 
-    pub fn num_steps(&self) -> usize {
+    // None if the map has no AAA node.
+    pub fn num_steps(&self) -> Option<usize> {
+        if !self.map.contains_key("AAA") {
+            return None;
+        }
         let mut state = "AAA".to_string();
         for (i, dir) in self.directions.iter().cycle().enumerate() {
             if state == "ZZZ" {
-                return i;
+                return Some(i);
             }
             state = self.next_state(&state, dir);
         }
-        0
+        Some(0)
     }
 
-    pub fn num_steps_ghost(&self) -> usize {
+    pub fn num_steps_ghost(&self) -> Option<usize> {
         let counts: Vec<usize> = self
             .ghost_start()
             .into_iter()
@@ -62,11 +87,11 @@ impl Map {
             .collect();
         counts
             .iter()
-            .fold(*counts.first().unwrap_or(&0), |a, b| a.lcm(b))
+            .try_fold(*counts.first().unwrap_or(&0), |a, b| checked_lcm(a, *b))
     }
 
-    fn num_steps_ghost_single(&self, init: &String) -> usize {
-        let mut state: String = init.clone();
+    fn num_steps_ghost_single(&self, init: &str) -> usize {
+        let mut state: String = init.to_string();
         for (i, dir) in self.directions.iter().cycle().enumerate() {
             if self.is_end(&state) {
                 return i;
@@ -76,6 +101,60 @@ impl Map {
         0
     }
 
+    pub fn steps_assumptions(&self) -> Vec<Assumption> {
+        let reaches = self.z_hits("AAA", |state| state == "ZZZ").is_some();
+        vec![Assumption::new("AAA reaches ZZZ", reaches)]
+    }
+
+    // The lcm of the ghosts' first Z hits is only right when every ghost then
+    // keeps returning to Z on that same period.
+    pub fn ghost_assumptions(&self) -> Vec<Assumption> {
+        let hits: Vec<Option<(usize, usize)>> = self
+            .ghost_start()
+            .iter()
+            .map(|state| self.z_hits(state, |state| self.is_end(state)))
+            .collect();
+        vec![
+            Assumption::new(
+                "every ghost reaches a node ending in Z",
+                hits.iter().all(Option::is_some),
+            ),
+            Assumption::new(
+                "each ghost's first Z hit equals its cycle length",
+                hits.iter()
+                    .all(|hit| matches!(hit, Some((first, next)) if *next == 2 * first)),
+            ),
+        ]
+    }
+
+    // Steps from a node to the first end node and on to the next one, or None
+    // if the walk starts repeating before reaching two.
+    fn z_hits(&self, init: &str, is_end: impl Fn(&str) -> bool) -> Option<(usize, usize)> {
+        if !self.map.contains_key(init) {
+            return None;
+        }
+        let limit = self.map.len() * self.directions.len();
+        let mut state = init.to_string();
+        let mut first = None;
+        for (i, dir) in self
+            .directions
+            .iter()
+            .cycle()
+            .enumerate()
+            .take(2 * limit + 1)
+        {
+            if is_end(&state) {
+                match first {
+                    None => first = Some(i),
+                    Some(first) if i > first => return Some((first, i)),
+                    Some(_) => {}
+                }
+            }
+            state = self.next_state(&state, dir);
+        }
+        None
+    }
+
     fn ghost_start(&self) -> Vec<String> {
         self.map
             .keys()
@@ -84,11 +163,11 @@ impl Map {
             .collect()
     }
 
-    fn is_end(&self, state: &String) -> bool {
+    fn is_end(&self, state: &str) -> bool {
         state.chars().nth(2) == Some('Z')
     }
 
-    fn next_state(&self, state: &String, dir: &Dir) -> String {
+    fn next_state(&self, state: &str, dir: &Dir) -> String {
         let (left, right) = self.map.get(state).unwrap();
         match dir {
             Dir::L => left.clone(),
@@ -96,3 +175,57 @@ impl Map {
         }
     }
 }
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for dir in &self.directions {
+            match dir {
+                Dir::L => write!(f, "L")?,
+                Dir::R => write!(f, "R")?,
+            }
+        }
+        writeln!(f)?;
+        let mut nodes: Vec<(&String, &(String, String))> = self.map.iter().collect();
+        nodes.sort();
+        for (source, (left, right)) in nodes {
+            write!(f, "\n{} = ({}, {})", source, left, right)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holds(assumptions: Vec<Assumption>) -> Vec<bool> {
+        assumptions
+            .iter()
+            .map(|assumption| assumption.holds)
+            .collect()
+    }
+
+    #[test]
+    fn aaa_must_reach_zzz() {
+        let reaching =
+            Map::from_string("LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+        assert_eq!(holds(reaching.unwrap().steps_assumptions()), [true]);
+        let circling =
+            Map::from_string("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)");
+        assert_eq!(holds(circling.unwrap().steps_assumptions()), [false]);
+    }
+
+    #[test]
+    fn ghosts_must_reach_z_on_their_period() {
+        let periodic = Map::from_string(
+            "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\n\
+            22A = (22B, XXX)\n22B = (22C, 22C)\n22C = (22Z, 22Z)\n\
+            22Z = (22B, 22B)\nXXX = (XXX, XXX)",
+        );
+        assert_eq!(holds(periodic.unwrap().ghost_assumptions()), [true, true]);
+        let offset = Map::from_string(
+            "L\n\n11A = (11Z, XXX)\n11Z = (11B, XXX)\n11B = (11Z, XXX)\nXXX = (XXX, XXX)",
+        );
+        assert_eq!(holds(offset.unwrap().ghost_assumptions()), [true, false]);
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use super::answer::{Answer, SolveError};
+use super::control::Control;
+use super::{challenge, io, writer};
+
+// What makes an input worth keeping while it is cut down.
+pub enum Predicate {
+    // The solver panics.
+    Panics,
+    // The solver fails with the same error as on the full input.
+    Errors,
+    // The registered implementations of the challenge all finish, but do not
+    // all give the same answer.
+    Disagrees,
+    // The shell command exits successfully with the input on its stdin.
+    Command(String),
+}
+
+impl Predicate {
+    pub fn from_string(string: &str) -> Option<Predicate> {
+        match string.split_once('=') {
+            Some(("cmd", command)) => Some(Predicate::Command(command.to_string())),
+            _ => match string {
+                "panic" => Some(Predicate::Panics),
+                "error" => Some(Predicate::Errors),
+                "disagree" => Some(Predicate::Disagrees),
+                _ => None,
+            },
+        }
+    }
+
+    // The original error is what the errors predicate looks for.
+    fn holds(&self, num: u8, input: &str, timeout: Duration, error: &Option<String>) -> bool {
+        match self {
+            Predicate::Panics => matches!(solve(num, input, timeout), Err(SolveError::Panic)),
+            Predicate::Errors => {
+                solve(num, input, timeout).is_err_and(|err| Some(err.to_string()) == *error)
+            }
+            Predicate::Disagrees => {
+                let answers: Vec<Answer> = challenge::implementations(num)
+                    .into_iter()
+                    .map(|name| solve_with(num, name, input, timeout))
+                    .collect::<Result<_, _>>()
+                    .unwrap_or_default();
+                answers.iter().any(|answer| *answer != answers[0])
+            }
+            Predicate::Command(command) => command_holds(command, input),
+        }
+    }
+}
+
+fn solve(num: u8, input: &str, timeout: Duration) -> Result<Answer, SolveError> {
+    solve_with(num, challenge::implementations(num)[0], input, timeout)
+}
+
+fn solve_with(num: u8, name: &str, input: &str, timeout: Duration) -> Result<Answer, SolveError> {
+    let control = Control::new().with_timeout(timeout);
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        challenge::challenge_impl(num, name, input, &control)
+    }))
+    .unwrap_or(Err(SolveError::Panic))
+}
+
+fn command_holds(command: &str, input: &str) -> bool {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that stops reading early still gets judged on its exit.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    child.wait().is_ok_and(|status| status.success())
+}
+
+// The initialization sequence is one line of comma separated steps, so the
+// steps are cut one by one instead of a line at a time.
+fn separator(day: u8) -> &'static str {
+    match day {
+        15 => ",",
+        _ => "\n",
+    }
+}
+
+// Cuts the input down to a smaller one the predicate still holds for,
+// deleting ever smaller runs of lines (or steps) until none can go. Inputs
+// are written out through the day's model where they parse, so the result
+// is in canonical form. None if the predicate does not hold for the input
+// to start with, or if the challenge does not exist.
+pub fn minimize(
+    day: u8,
+    part: u8,
+    input: &str,
+    predicate: &Predicate,
+    timeout: Duration,
+) -> Option<String> {
+    let num = challenge::number(day, part)?;
+    // Panics are expected here, so keep their messages off the terminal.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let input = io::normalize(input);
+    let error = match predicate {
+        Predicate::Errors => solve(num, &input, timeout).err().map(|err| err.to_string()),
+        _ => None,
+    };
+    let fails = |input: &str| predicate.holds(num, input, timeout, &error);
+    let minimized = fails(&input).then(|| {
+        let canonical = |text: String| match writer::write(day, &text) {
+            Ok(written) if fails(&written) => written,
+            _ => text,
+        };
+        let sep = separator(day);
+        let current = canonical(input.clone());
+        let mut units: Vec<&str> = current.split(sep).collect();
+        let mut chunk = (units.len() / 2).max(1);
+        loop {
+            let mut removed = false;
+            let mut i = 0;
+            while i < units.len() {
+                let end = (i + chunk).min(units.len());
+                let candidate = [&units[..i], &units[end..]].concat().join(sep);
+                if fails(&candidate) {
+                    units.drain(i..end);
+                    removed = true;
+                } else {
+                    i += chunk;
+                }
+            }
+            if chunk == 1 && !removed {
+                break;
+            }
+            if !removed {
+                chunk /= 2;
+            }
+            chunk = chunk.min(units.len() / 2).max(1);
+        }
+        canonical(units.join(sep))
+    });
+    panic::set_hook(hook);
+    minimized
+}
@@ -1,8 +1,13 @@
-use std::cmp::max;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
-use super::utils::Dir;
+use super::simulation::Simulation;
+use super::utils::{render, Dir};
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Mirror {
     DiagUR,
     DiagUL,
@@ -10,12 +15,30 @@ enum Mirror {
     Vertical,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MirrorCave {
+    #[cfg_attr(feature = "serde", serde(with = "crate::grid_serde"))]
     mirrors: HashMap<(i64, i64), Mirror>,
     width: i64,
     height: i64,
 }
 
+// The beam from the top left part way across a cave, kept apart from the cave
+// so the parsed model holds only the puzzle.
+pub struct Lighting<'a> {
+    cave: &'a MirrorCave,
+    trace: Trace,
+}
+
+// Every tile and direction the beam has passed through, and the front it
+// will move on from.
+#[derive(Clone, Default)]
+pub struct Trace {
+    beams: HashSet<(i64, i64, Dir)>,
+    next: Vec<(i64, i64, Dir)>,
+}
+
 impl Mirror {
     fn from_char(ch: char) -> Option<Mirror> {
         match ch {
@@ -26,6 +49,15 @@ impl Mirror {
             _ => None,
         }
     }
+
+    fn to_char(&self) -> char {
+        match self {
+            Mirror::DiagUR => '/',
+            Mirror::DiagUL => '\\',
+            Mirror::Horizontal => '-',
+            Mirror::Vertical => '|',
+        }
+    }
 }
 
 impl MirrorCave {
@@ -43,55 +75,76 @@ impl MirrorCave {
         }
     }
 
+    // The beam from the top left, yet to enter the cave.
+    pub fn lighting(&self) -> Lighting<'_> {
+        Lighting {
+            cave: self,
+            trace: Trace {
+                beams: HashSet::new(),
+                next: vec![(0, 0, Dir::Right)],
+            },
+        }
+    }
+
     pub fn simulate_excited(&self) -> usize {
         self.simulate_excited_start(0, 0, Dir::Right)
     }
 
     fn simulate_excited_start(&self, i: i64, j: i64, dir: Dir) -> usize {
+        self.excited(i, j, dir).len()
+    }
+
+    fn excited(&self, i: i64, j: i64, dir: Dir) -> HashSet<(i64, i64)> {
         let mut beams: HashSet<(i64, i64, Dir)> = HashSet::new();
         let mut next: Vec<(i64, i64, Dir)> = vec![(i, j, dir)];
-        while !next.is_empty() {
-            let (ni, nj, n_dir) = next.pop().unwrap();
+        while let Some((ni, nj, n_dir)) = next.pop() {
             self.simulate_beam(ni, nj, n_dir, &mut beams, &mut next);
         }
-        let excited: HashSet<(i64, i64)> = beams.iter().map(|(i, j, _)| (*i, *j)).collect();
-        excited.len()
-    }
-
-    pub fn max_simulate_excited(&self) -> usize {
-        let m_up = self.max_simulate_excited_up();
-        let m_down = self.max_simulate_excited_down();
-        let m_left = self.max_simulate_excited_left();
-        let m_right = self.max_simulate_excited_right();
-        max(max(m_up, m_down), max(m_left, m_right))
+        beams.iter().map(|(i, j, _)| (*i, *j)).collect()
     }
 
-    fn max_simulate_excited_up(&self) -> usize {
-        (0..self.width)
-            .map(|j| self.simulate_excited_start(self.height - 1, j, Dir::Up))
-            .max()
-            .unwrap_or(0)
+    // The cave with the tiles energized by the beam from the top left as #.
+    pub fn render_excited(&self) -> String {
+        let excited = self.excited(0, 0, Dir::Right);
+        render(self.height, self.width, |i, j| {
+            match (excited.contains(&(i, j)), self.mirrors.get(&(i, j))) {
+                (true, _) => '#',
+                (false, Some(mirror)) => mirror.to_char(),
+                (false, None) => '.',
+            }
+        })
     }
 
-    fn max_simulate_excited_down(&self) -> usize {
-        (0..self.width)
-            .map(|j| self.simulate_excited_start(0, j, Dir::Down))
+    // Every beam start is simulated independently, so with the parallel
+    // feature they are spread across threads.
+    pub fn max_simulate_excited(&self) -> usize {
+        let edge_starts = self.edge_starts();
+        #[cfg(feature = "parallel")]
+        let starts = edge_starts.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let starts = edge_starts.iter();
+        starts
+            .map(|&(i, j, dir)| self.simulate_excited_start(i, j, dir))
             .max()
             .unwrap_or(0)
     }
 
-    fn max_simulate_excited_left(&self) -> usize {
-        (0..self.height)
-            .map(|i| self.simulate_excited_start(i, self.width - 1, Dir::Left))
-            .max()
-            .unwrap_or(0)
+    // Every position on the edge, facing into the cave.
+    fn edge_starts(&self) -> Vec<(i64, i64, Dir)> {
+        let mut starts = Vec::new();
+        for j in 0..self.width {
+            starts.push((self.height - 1, j, Dir::Up));
+            starts.push((0, j, Dir::Down));
+        }
+        for i in 0..self.height {
+            starts.push((i, self.width - 1, Dir::Left));
+            starts.push((i, 0, Dir::Right));
+        }
+        starts
     }
 
-    fn max_simulate_excited_right(&self) -> usize {
-        (0..self.height)
-            .map(|i| self.simulate_excited_start(i, 0, Dir::Right))
-            .max()
-            .unwrap_or(0)
+    fn inside(&self, i: i64, j: i64) -> bool {
+        (0..self.height).contains(&i) && (0..self.width).contains(&j)
     }
 
     fn simulate_beam(
@@ -102,10 +155,7 @@ impl MirrorCave {
         beams: &mut HashSet<(i64, i64, Dir)>,
         next: &mut Vec<(i64, i64, Dir)>,
     ) {
-        if i < 0 || i >= self.height {
-            return;
-        }
-        if j < 0 || j >= self.width {
+        if !self.inside(i, j) {
             return;
         }
         if beams.contains(&(i, j, dir)) {
@@ -123,28 +173,76 @@ impl MirrorCave {
                 let (ni, nj) = new_dir.step(i, j);
                 next.push((ni, nj, new_dir));
             }
-            Some(Mirror::Horizontal) => {
-                if dir == Dir::Up || dir == Dir::Down {
-                    next.push((i, j - 1, Dir::Left));
-                    next.push((i, j + 1, Dir::Right));
-                } else {
-                    let (ni, nj) = dir.step(i, j);
-                    next.push((ni, nj, dir));
-                }
+            Some(Mirror::Horizontal) if dir == Dir::Up || dir == Dir::Down => {
+                next.push((i, j - 1, Dir::Left));
+                next.push((i, j + 1, Dir::Right));
             }
-            Some(Mirror::Vertical) => {
-                if dir == Dir::Left || dir == Dir::Right {
-                    next.push((i - 1, j, Dir::Up));
-                    next.push((i + 1, j, Dir::Down));
-                } else {
-                    let (ni, nj) = dir.step(i, j);
-                    next.push((ni, nj, dir));
-                }
+            Some(Mirror::Vertical) if dir == Dir::Left || dir == Dir::Right => {
+                next.push((i - 1, j, Dir::Up));
+                next.push((i + 1, j, Dir::Down));
             }
-            None => {
+            _ => {
                 let (ni, nj) = dir.step(i, j);
                 next.push((ni, nj, dir));
             }
         }
     }
 }
+
+// A tick moves every beam on the front on by one tile.
+impl Simulation for Lighting<'_> {
+    type Snapshot = Trace;
+
+    fn step(&mut self) -> bool {
+        let cave = self.cave;
+        let trace = &mut self.trace;
+        let front = std::mem::take(&mut trace.next);
+        for (i, j, dir) in front {
+            cave.simulate_beam(i, j, dir, &mut trace.beams, &mut trace.next);
+        }
+        // Beams leaving the cave or retracing a path drop off the front.
+        trace
+            .next
+            .retain(|(i, j, dir)| cave.inside(*i, *j) && !trace.beams.contains(&(*i, *j, *dir)));
+        !trace.next.is_empty()
+    }
+
+    fn snapshot(&self) -> Trace {
+        self.trace.clone()
+    }
+
+    fn restore(&mut self, snapshot: &Trace) {
+        self.trace = snapshot.clone();
+    }
+
+    fn state(&self) -> String {
+        let cave = self.cave;
+        let front: HashMap<(i64, i64), Dir> = self
+            .trace
+            .next
+            .iter()
+            .map(|(i, j, dir)| ((*i, *j), *dir))
+            .collect();
+        let excited: HashSet<(i64, i64)> =
+            self.trace.beams.iter().map(|(i, j, _)| (*i, *j)).collect();
+        render(cave.height, cave.width, |i, j| {
+            match (front.get(&(i, j)), cave.mirrors.get(&(i, j))) {
+                (Some(dir), None) => dir.to_char(),
+                (_, Some(mirror)) => mirror.to_char(),
+                (None, None) if excited.contains(&(i, j)) => '#',
+                (None, None) => '.',
+            }
+        })
+    }
+}
+
+impl fmt::Display for MirrorCave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = render(self.height, self.width, |i, j| {
+            self.mirrors
+                .get(&(i, j))
+                .map_or('.', |mirror| mirror.to_char())
+        });
+        write!(f, "{}", grid)
+    }
+}
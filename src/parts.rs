@@ -1,34 +1,47 @@
-use std::collections::HashSet;
+use crate::answer::{checked_sum, checked_sum_opt};
+use crate::utils::render;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 type Point = (usize, usize);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct PartNumber {
     base: Point,
     length: u32,
     value: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Gear {
     nums: (PartNumber, PartNumber),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Engine {
+    // Every number in the schematic, next to a symbol or not.
+    numbers: Vec<PartNumber>,
     part_numbers: Vec<PartNumber>,
     gears: Vec<Gear>,
+    // Kept so the schematic can be written back out.
+    #[cfg_attr(feature = "serde", serde(with = "crate::grid_serde"))]
+    symbols: HashMap<Point, char>,
+    width: usize,
+    height: usize,
 }
 
 fn is_symbol(ch: &char) -> bool {
     !ch.is_ascii_digit() && *ch != '.'
 }
 
-fn symbols_from_line(line: &str, i: usize) -> Vec<Point> {
+fn symbols_from_line(line: &str, i: usize) -> Vec<(Point, char)> {
     line.chars()
         .enumerate()
         .filter(|(_, ch)| is_symbol(ch))
-        .map(|(j, _)| (i, j))
+        .map(|(j, ch)| ((i, j), ch))
         .collect()
 }
 
@@ -40,13 +53,13 @@ fn star_from_line(line: &str, i: usize) -> Vec<Point> {
         .collect()
 }
 
-fn part_nums_from_line(line: &str, i: usize) -> Vec<PartNumber> {
+fn part_nums_from_line(line: &str, i: usize) -> Option<Vec<PartNumber>> {
     let mut nums: Vec<PartNumber> = Vec::new();
     let mut opt_num: Option<PartNumber> = None;
     for (j, ch) in line.chars().enumerate() {
         if let Some(val) = super::calibration::is_num(ch) {
             if let Some(num) = &mut opt_num {
-                num.extend(val);
+                num.extend(val)?;
             } else {
                 opt_num = Some(PartNumber::one(val, i, j))
             }
@@ -60,7 +73,7 @@ fn part_nums_from_line(line: &str, i: usize) -> Vec<PartNumber> {
     if let Some(num) = &opt_num {
         nums.push(num.clone());
     }
-    nums
+    Some(nums)
 }
 
 impl PartNumber {
@@ -72,14 +85,15 @@ impl PartNumber {
         }
     }
 
-    fn extend(&mut self, val: u8) {
+    fn extend(&mut self, val: u8) -> Option<()> {
         self.length += 1;
-        self.value = (self.value * 10) + (val as u32);
+        self.value = self.value.checked_mul(10)?.checked_add(val as u32)?;
+        Some(())
     }
 
     fn adjacent_pt(&self, pt: &Point) -> bool {
         let dx = pt.0 as i32 - self.base.0 as i32;
-        if dx < -1 || dx > 1 {
+        if !(-1..=1).contains(&dx) {
             return false;
         }
         let dy = pt.1 as i32 - self.base.1 as i32;
@@ -109,7 +123,7 @@ impl PartNumber {
 }
 
 impl Gear {
-    fn from_pt(pt: Point, parts: &Vec<PartNumber>) -> Option<Gear> {
+    fn from_pt(pt: Point, parts: &[PartNumber]) -> Option<Gear> {
         let adj: Vec<PartNumber> = parts
             .iter()
             .filter(|part| part.adjacent_pt(&pt))
@@ -126,50 +140,77 @@ impl Gear {
 }
 
 impl Engine {
-    pub fn from_lines(lines: &Vec<String>) -> Engine {
-        let symbols: HashSet<Point> = lines
+    pub fn from_lines(lines: &[String]) -> Option<Engine> {
+        let symbols: HashMap<Point, char> = lines
             .iter()
             .enumerate()
-            .map(|(i, line)| symbols_from_line(line, i))
-            .flatten()
+            .flat_map(|(i, line)| symbols_from_line(line, i))
             .collect();
+        let symbol_points: HashSet<Point> = symbols.keys().cloned().collect();
         let numbers: Vec<PartNumber> = lines
             .iter()
             .enumerate()
             .map(|(i, line)| part_nums_from_line(line, i))
+            .collect::<Option<Vec<Vec<PartNumber>>>>()?
+            .into_iter()
             .flatten()
             .collect();
-        let part_numbers = numbers
-            .into_iter()
-            .filter(|number| number.adjacent_symbol(&symbols))
+        let part_numbers: Vec<PartNumber> = numbers
+            .iter()
+            .filter(|number| number.adjacent_symbol(&symbol_points))
+            .cloned()
             .collect();
         let stars: Vec<Point> = lines
             .iter()
             .enumerate()
-            .map(|(i, line)| star_from_line(line, i))
-            .flatten()
+            .flat_map(|(i, line)| star_from_line(line, i))
             .collect();
         let gears = stars
             .into_iter()
             .filter_map(|pt| Gear::from_pt(pt, &part_numbers))
             .collect();
-        Engine {
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        Some(Engine {
+            numbers,
             part_numbers,
             gears,
-        }
+            symbols,
+            width,
+            height: lines.len(),
+        })
     }
 
-    pub fn sum_part_numbers(&self) -> usize {
-        self.part_numbers
-            .iter()
-            .map(|number| number.value as usize)
-            .sum()
+    pub fn sum_part_numbers(&self) -> Option<usize> {
+        checked_sum(self.part_numbers.iter().map(|number| number.value as usize))
     }
 
-    pub fn sum_gears(&self) -> usize {
-        self.gears
-            .iter()
-            .map(|gear| gear.nums.0.value as usize * gear.nums.1.value as usize)
-            .sum()
+    pub fn sum_gears(&self) -> Option<usize> {
+        checked_sum_opt(
+            self.gears
+                .iter()
+                .map(|gear| (gear.nums.0.value as usize).checked_mul(gear.nums.1.value as usize)),
+        )
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut digits: HashMap<Point, char> = HashMap::new();
+        for number in &self.numbers {
+            let (i, j) = number.base;
+            let text = format!("{:0width$}", number.value, width = number.length as usize);
+            for (k, ch) in text.chars().enumerate() {
+                digits.insert((i, j + k), ch);
+            }
+        }
+        let grid = render(self.height as i64, self.width as i64, |i, j| {
+            let pt = (i as usize, j as usize);
+            *digits.get(&pt).or(self.symbols.get(&pt)).unwrap_or(&'.')
+        });
+        write!(f, "{}", grid)
     }
 }
@@ -1,8 +1,12 @@
+use crate::assumption::Assumption;
+use crate::utils::render;
 use itertools::Itertools;
 use num::traits::FloatConst;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Pipe {
     Start,
     NS,
@@ -29,74 +33,81 @@ impl Pipe {
 }
 
 impl Pipe {
-    fn connect_south(&self) -> bool {
+    fn to_char(&self) -> char {
         match self {
-            Pipe::Start => true,
-            Pipe::NS => true,
-            Pipe::SE => true,
-            Pipe::SW => true,
-            _ => false,
+            Pipe::Start => 'S',
+            Pipe::NS => '|',
+            Pipe::EW => '-',
+            Pipe::NE => 'L',
+            Pipe::NW => 'J',
+            Pipe::SW => '7',
+            Pipe::SE => 'F',
         }
     }
 
-    fn connect_north(&self) -> bool {
+    // Box drawing characters make the loop easy to follow.
+    fn to_box_char(&self) -> char {
         match self {
-            Pipe::Start => true,
-            Pipe::NS => true,
-            Pipe::NE => true,
-            Pipe::NW => true,
-            _ => false,
+            Pipe::Start => 'S',
+            Pipe::NS => '│',
+            Pipe::EW => '─',
+            Pipe::NE => '└',
+            Pipe::NW => '┘',
+            Pipe::SE => '┌',
+            Pipe::SW => '┐',
         }
     }
 
+    fn connect_south(&self) -> bool {
+        matches!(self, Pipe::Start | Pipe::NS | Pipe::SE | Pipe::SW)
+    }
+
+    fn connect_north(&self) -> bool {
+        matches!(self, Pipe::Start | Pipe::NS | Pipe::NE | Pipe::NW)
+    }
+
     fn connect_east(&self) -> bool {
-        match self {
-            Pipe::Start => true,
-            Pipe::EW => true,
-            Pipe::SE => true,
-            Pipe::NE => true,
-            _ => false,
-        }
+        matches!(self, Pipe::Start | Pipe::EW | Pipe::SE | Pipe::NE)
     }
 
     fn connect_west(&self) -> bool {
-        match self {
-            Pipe::Start => true,
-            Pipe::EW => true,
-            Pipe::SW => true,
-            Pipe::NW => true,
-            _ => false,
-        }
+        matches!(self, Pipe::Start | Pipe::EW | Pipe::SW | Pipe::NW)
     }
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pipes {
+    #[cfg_attr(feature = "serde", serde(with = "crate::grid_serde"))]
     pipes: HashMap<(i64, i64), Pipe>,
     max_i: i64,
     max_j: i64,
 }
 
 impl Pipes {
-    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Pipes {
+    // None unless the grid has exactly one start tile.
+    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Option<Pipes> {
         let pipes: HashMap<(i64, i64), Pipe> = grid
             .iter()
             .filter_map(|((i, j), ch)| Pipe::from_char(*ch).map(|pipe| ((*i, *j), pipe)))
             .collect();
+        if pipes.values().filter(|pipe| **pipe == Pipe::Start).count() != 1 {
+            return None;
+        }
         let max_i = pipes.keys().map(|(i, _)| *i).max().unwrap_or(0);
         let max_j = pipes.keys().map(|(_, j)| *j).max().unwrap_or(0);
-        Pipes {
+        Some(Pipes {
             pipes,
             max_i,
             max_j,
-        }
+        })
     }
 
     fn start(&self) -> (i64, i64) {
         let ((i, j), _) = self
             .pipes
             .iter()
-            .filter(|(_, pipe)| **pipe == Pipe::Start)
-            .next()
+            .find(|(_, pipe)| **pipe == Pipe::Start)
             .unwrap();
         (*i, *j)
     }
@@ -148,9 +159,74 @@ impl Pipes {
         ordered
     }
 
+    // The loop through S, with every other tile blank.
+    pub fn render_loop(&self) -> String {
+        let (_, pipe_set) = self.pipe_loop();
+        render(self.max_i + 1, self.max_j + 1, |i, j| {
+            match (pipe_set.contains(&(i, j)), self.pipes.get(&(i, j))) {
+                (true, Some(pipe)) => pipe.to_box_char(),
+                _ => ' ',
+            }
+        })
+    }
+
+    pub fn assumptions(&self) -> Vec<Assumption> {
+        let (i, j) = self.start();
+        vec![
+            Assumption::new(
+                "exactly two pipes connect to S",
+                self.connected(i, j).len() == 2,
+            ),
+            Assumption::new(
+                "the pipes from S form a single closed loop",
+                self.closes_loop(),
+            ),
+        ]
+    }
+
+    // Follows the pipes out of S, checking each one leads on from the last.
+    fn closes_loop(&self) -> bool {
+        let start = self.start();
+        let Some(&first) = self.connected(start.0, start.1).first() else {
+            return false;
+        };
+        let (mut prev, mut curr) = (start, first);
+        for _ in 0..self.pipes.len() {
+            if curr == start {
+                return true;
+            }
+            let Some(ends) = self.ends(curr) else {
+                return false;
+            };
+            if !ends.contains(&prev) {
+                return false;
+            }
+            let Some(&next) = ends.iter().find(|end| **end != prev) else {
+                return false;
+            };
+            (prev, curr) = (curr, next);
+        }
+        false
+    }
+
     fn next(&self, (i, j): (i64, i64), prev: Option<(i64, i64)>) -> (i64, i64) {
+        let vec = self.ends((i, j)).unwrap();
+        assert_eq!(vec.len(), 2);
+        if let Some(prev_i) = prev {
+            if vec[0] == prev_i {
+                vec[1]
+            } else {
+                vec[0]
+            }
+        } else {
+            vec[0]
+        }
+    }
+
+    // Where the pipe at a position leads, None if there is no pipe.
+    fn ends(&self, (i, j): (i64, i64)) -> Option<Vec<(i64, i64)>> {
         let mut vec: Vec<(i64, i64)> = Vec::new();
-        match self.pipes.get(&(i, j)).unwrap() {
+        match self.pipes.get(&(i, j))? {
             Pipe::Start => {
                 vec = self.connected(i, j);
             }
@@ -179,19 +255,10 @@ impl Pipes {
                 vec.push((i, j - 1));
             }
         }
-        assert_eq!(vec.len(), 2);
-        if let Some(prev_i) = prev {
-            if vec[0] == prev_i {
-                vec[1]
-            } else {
-                vec[0]
-            }
-        } else {
-            vec[0]
-        }
+        Some(vec)
     }
 
-    fn inside_loop(&self, i: i64, j: i64, pipe_loop: &Vec<(i64, i64)>) -> bool {
+    fn inside_loop(&self, i: i64, j: i64, pipe_loop: &[(i64, i64)]) -> bool {
         let mut winding: f64 = 0.0;
         let mut prev_angle: Option<f64> = None;
         for point in pipe_loop.iter() {
@@ -208,7 +275,7 @@ impl Pipes {
             }
             prev_angle = Some(angle);
         }
-        return winding.abs() > 1e-6;
+        winding.abs() > 1e-6
     }
 
     fn connected_union(&self, elems: &HashSet<(i64, i64)>) -> HashSet<(i64, i64)> {
@@ -247,3 +314,37 @@ impl Pipes {
         found
     }
 }
+
+impl fmt::Display for Pipes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = render(self.max_i + 1, self.max_j + 1, |i, j| {
+            self.pipes.get(&(i, j)).map_or('.', |pipe| pipe.to_char())
+        });
+        write!(f, "{}", grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io;
+
+    fn holds(text: &str) -> Vec<bool> {
+        let pipes = Pipes::from_grid(&io::grid(text)).unwrap();
+        pipes
+            .assumptions()
+            .iter()
+            .map(|assumption| assumption.holds)
+            .collect()
+    }
+
+    #[test]
+    fn a_closed_loop_holds() {
+        assert_eq!(holds(".....\n.S-7.\n.|.|.\n.L-J.\n....."), [true, true]);
+    }
+
+    #[test]
+    fn a_loop_with_a_gap_does_not() {
+        assert_eq!(holds(".....\n.S-7.\n.|.|.\n.L-..\n....."), [true, false]);
+    }
+}
@@ -0,0 +1,88 @@
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyOverflowError, PyValueError};
+use pyo3::prelude::*;
+
+use super::answer::Answer;
+use super::challenge;
+use super::control::Control;
+use super::io;
+use super::seeds;
+use super::workflow;
+
+create_exception!(advent_of_code, SolveError, PyException);
+
+fn answer_object(py: Python<'_>, answer: Answer) -> PyResult<Py<PyAny>> {
+    let object = match answer {
+        Answer::U32(val) => val.into_pyobject(py)?.into_any(),
+        Answer::U64(val) => val.into_pyobject(py)?.into_any(),
+        Answer::I64(val) => val.into_pyobject(py)?.into_any(),
+        Answer::Text(val) => val.into_pyobject(py)?.into_any(),
+    };
+    Ok(object.unbind())
+}
+
+#[pyfunction]
+fn solve(py: Python<'_>, day: u8, part: u8, input: &str) -> PyResult<Py<PyAny>> {
+    let result = py.detach(|| challenge::solve(day, part, input, &Control::new()));
+    match result {
+        Ok(answer) => answer_object(py, answer),
+        Err(err) => Err(SolveError::new_err(err.to_string())),
+    }
+}
+
+#[pyclass(name = "Almanac")]
+struct PyAlmanac(seeds::Almanac);
+
+#[pymethods]
+impl PyAlmanac {
+    #[new]
+    fn new(input: &str) -> PyResult<PyAlmanac> {
+        seeds::Almanac::from_string(&io::normalize(input))
+            .map(PyAlmanac)
+            .ok_or_else(|| PyValueError::new_err("failed to parse almanac"))
+    }
+
+    fn least_site(&self) -> usize {
+        self.0.least_site()
+    }
+
+    fn least_site_ranges(&self) -> PyResult<usize> {
+        self.0
+            .least_site_ranges()
+            .ok_or_else(|| PyValueError::new_err("seeds do not pair up into ranges"))
+    }
+}
+
+#[pyclass(name = "Pile")]
+struct PyPile(workflow::Pile);
+
+#[pymethods]
+impl PyPile {
+    #[new]
+    fn new(input: &str) -> PyResult<PyPile> {
+        workflow::Pile::from_string(&io::normalize(input))
+            .map(PyPile)
+            .ok_or_else(|| PyValueError::new_err("failed to parse workflows and parts"))
+    }
+
+    fn accepted_ratings(&self) -> PyResult<i64> {
+        self.0
+            .accepted_ratings()
+            .ok_or_else(|| PyOverflowError::new_err("answer overflowed"))
+    }
+
+    fn accepted_combos(&self) -> PyResult<i64> {
+        self.0
+            .accepted_combos()
+            .ok_or_else(|| PyOverflowError::new_err("answer overflowed"))
+    }
+}
+
+#[pymodule]
+fn advent_of_code(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_class::<PyAlmanac>()?;
+    m.add_class::<PyPile>()?;
+    m.add("SolveError", m.py().get_type::<SolveError>())?;
+    Ok(())
+}
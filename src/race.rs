@@ -1,3 +1,8 @@
+use crate::answer::checked_product;
+use std::fmt;
+
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Race {
     time: usize,
     distance: usize,
@@ -13,8 +18,7 @@ fn merge_line(line: &str) -> Option<usize> {
 impl Race {
     pub fn num_success(&self) -> usize {
         if let Some((min, max)) = self.range() {
-            let v = 1 + max - min;
-            v
+            1 + max - min
         } else {
             0
         }
@@ -30,16 +34,16 @@ impl Race {
         let root = discriminant.sqrt();
         let min = (t - root) * 0.5;
         let max = (t + root) * 0.5;
-        let min_int = min.floor() as usize + 1;
-        let max_int = max.ceil() as usize - 1;
+        let min_int = (min.floor() as usize).checked_add(1)?;
+        let max_int = (max.ceil() as usize).checked_sub(1)?;
         if min_int > max_int {
             return None;
         }
         Some((min_int, max_int))
     }
 
-    pub fn from_lines(lines: &Vec<String>) -> Option<Race> {
-        let time_line = lines.get(0)?;
+    pub fn from_lines(lines: &[String]) -> Option<Race> {
+        let time_line = lines.first()?;
         let time_str = time_line.strip_prefix("Time:")?;
         let time = merge_line(time_str)?;
         let dist_line = lines.get(1)?;
@@ -49,6 +53,8 @@ impl Race {
     }
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Races {
     races: Vec<Race>,
 }
@@ -61,8 +67,8 @@ fn split_line(line: &str) -> Option<Vec<usize>> {
 }
 
 impl Races {
-    pub fn from_lines(lines: &Vec<String>) -> Option<Races> {
-        let time_line = lines.get(0)?;
+    pub fn from_lines(lines: &[String]) -> Option<Races> {
+        let time_line = lines.first()?;
         let time_str = time_line.strip_prefix("Time:")?;
         let times = split_line(time_str)?;
         let dist_line = lines.get(1)?;
@@ -79,7 +85,41 @@ impl Races {
         Some(Races { races })
     }
 
-    pub fn score(&self) -> usize {
-        self.races.iter().map(|race| race.num_success()).product()
+    pub fn score(&self) -> Option<usize> {
+        checked_product(self.races.iter().map(|race| race.num_success()))
+    }
+}
+
+// The time and distance lines, with each race in a right aligned column.
+fn table(f: &mut fmt::Formatter, races: &[&Race]) -> fmt::Result {
+    let widths: Vec<usize> = races
+        .iter()
+        .map(|race| {
+            race.time
+                .to_string()
+                .len()
+                .max(race.distance.to_string().len())
+        })
+        .collect();
+    write!(f, "Time:    ")?;
+    for (race, width) in races.iter().zip(&widths) {
+        write!(f, " {:>width$}", race.time, width = width)?;
+    }
+    write!(f, "\nDistance:")?;
+    for (race, width) in races.iter().zip(&widths) {
+        write!(f, " {:>width$}", race.distance, width = width)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Race {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        table(f, &[self])
+    }
+}
+
+impl fmt::Display for Races {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        table(f, &self.races.iter().collect::<Vec<&Race>>())
     }
 }
@@ -0,0 +1,19 @@
+use super::answer::SolveError;
+use super::*;
+
+// Text frames a day's model can show, such as a grid part way through its
+// simulation. Days without a rendering of their own show their input.
+pub fn frames(day: u8, input: &str) -> Result<Vec<String>, SolveError> {
+    let input = io::normalize(input);
+    match day {
+        10 => {
+            let pipes = pipes::Pipes::from_grid(&io::grid(&input)).ok_or(SolveError::Parse)?;
+            Ok(vec![pipes.render_loop()])
+        }
+        14 => Ok(beams::Beam::from_grid(&io::grid(&input)).spin_frames()),
+        16 => Ok(vec![
+            mirrors::MirrorCave::from_grid(&io::grid(&input)).render_excited()
+        ]),
+        _ => Ok(vec![input]),
+    }
+}
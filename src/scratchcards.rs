@@ -1,10 +1,17 @@
+use crate::answer::checked_sum_opt;
+use crate::utils::join;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct ScratchCard {
     numbers: Vec<usize>,
     winning: HashSet<usize>,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScratchCards {
     cards: Vec<ScratchCard>,
 }
@@ -24,12 +31,12 @@ impl ScratchCard {
         Some(ScratchCard { winning, numbers })
     }
 
-    fn winnings(&self) -> usize {
+    fn winnings(&self) -> Option<usize> {
         let overlap = self.winning_count();
         if overlap == 0 {
-            return 0;
+            return Some(0);
         }
-        2_u64.pow((overlap - 1) as u32) as usize
+        2_usize.checked_pow(u32::try_from(overlap - 1).ok()?)
     }
 
     fn winning_count(&self) -> usize {
@@ -41,7 +48,7 @@ impl ScratchCard {
 }
 
 impl ScratchCards {
-    pub fn from_lines(lines: &Vec<String>) -> Option<ScratchCards> {
+    pub fn from_lines(lines: &[String]) -> Option<ScratchCards> {
         let cards = lines
             .iter()
             .map(|line| ScratchCard::from_line(line))
@@ -49,33 +56,54 @@ impl ScratchCards {
         Some(ScratchCards { cards })
     }
 
-    pub fn winnings(&self) -> usize {
-        self.cards.iter().map(|card| card.winnings()).sum()
+    pub fn winnings(&self) -> Option<usize> {
+        checked_sum_opt(self.cards.iter().map(|card| card.winnings()))
     }
 
-    pub fn winning_scratchcards(&self) -> usize {
+    pub fn winning_scratchcards(&self) -> Option<usize> {
         let mut cached_winning: HashMap<usize, usize> = HashMap::new();
         let mut sum: usize = 0;
         for (i, _) in self.cards.iter().enumerate() {
-            sum += self.winning_scratchcard(i, &mut cached_winning);
+            sum = sum.checked_add(self.winning_scratchcard(i, &mut cached_winning)?)?;
         }
-        sum
+        Some(sum)
     }
 
-    fn winning_scratchcard(&self, i: usize, cache: &mut HashMap<usize, usize>) -> usize {
+    fn winning_scratchcard(&self, i: usize, cache: &mut HashMap<usize, usize>) -> Option<usize> {
         if let Some(val) = cache.get(&i) {
-            return *val;
+            return Some(*val);
         }
         if let Some(card) = self.cards.get(i) {
             let mut sum: usize = 1;
             let overlap = card.winning_count();
             for j in 0..overlap {
-                sum += self.winning_scratchcard(i + j + 1, cache);
+                sum = sum.checked_add(self.winning_scratchcard(i + j + 1, cache)?)?;
             }
             cache.insert(i, sum);
-            sum
+            Some(sum)
         } else {
-            0
+            Some(0)
         }
     }
 }
+
+fn numbers(f: &mut fmt::Formatter, numbers: &[usize]) -> fmt::Result {
+    join(f, numbers.iter().map(|num| format!("{:>2}", num)), " ")
+}
+
+impl fmt::Display for ScratchCards {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, card) in self.cards.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let mut winning: Vec<usize> = card.winning.iter().cloned().collect();
+            winning.sort();
+            write!(f, "Card {}: ", i + 1)?;
+            numbers(f, &winning)?;
+            write!(f, " | ")?;
+            numbers(f, &card.numbers)?;
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,19 @@
+use crate::utils::join;
+use std::fmt;
+
 struct Ranges {
     vals: Vec<(usize, usize)>,
 }
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct RangeMap {
+    // The header without its trailing " map:", such as seed-to-soil.
+    name: String,
     map: Vec<(usize, usize, usize)>,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Almanac {
     seeds: Vec<usize>,
     maps: Vec<RangeMap>,
@@ -32,7 +41,8 @@ impl Ranges {
 
 impl RangeMap {
     fn from_string(string: &str) -> Option<RangeMap> {
-        let lines = string.lines().skip(1);
+        let mut lines = string.lines();
+        let name = lines.next()?.strip_suffix(" map:")?.to_string();
         let mut map: Vec<(usize, usize, usize)> = Vec::new();
         for line in lines {
             let mut parts = line.split(" ");
@@ -42,15 +52,17 @@ impl RangeMap {
             let source = source_str.parse::<usize>().ok()?;
             let len_str = parts.next()?;
             let len = len_str.parse::<usize>().ok()?;
+            source.checked_add(len)?;
+            dest.checked_add(len)?;
             map.push((source, dest, len));
         }
         map.sort();
-        Some(RangeMap { map })
+        Some(RangeMap { name, map })
     }
 
     fn map(&self, val: usize) -> usize {
         for (source, dest, len) in &self.map {
-            if *source <= val && val < source + len {
+            if *source <= val && val - source < *len {
                 let offset = val - source;
                 return dest + offset;
             }
@@ -122,8 +134,11 @@ impl Almanac {
             .split(" ")
             .map(|seed_str| seed_str.parse::<usize>().ok())
             .collect::<Option<Vec<usize>>>()?;
+        if seeds.is_empty() {
+            return None;
+        }
         let maps = chunks
-            .map(|chunk| RangeMap::from_string(chunk))
+            .map(RangeMap::from_string)
             .collect::<Option<Vec<RangeMap>>>()?;
         Some(Almanac { seeds, maps })
     }
@@ -136,13 +151,14 @@ impl Almanac {
         val
     }
 
-    fn seed_range_to_site(&self, base: usize, len: usize) -> usize {
+    fn seed_range_to_site(&self, base: usize, len: usize) -> Option<usize> {
+        base.checked_add(len)?;
         let mut val = Ranges::empty();
         val.add(base, len);
         for map in &self.maps {
             val = map.map_ranges(&val);
         }
-        val.min()
+        Some(val.min())
     }
 
     pub fn least_site(&self) -> usize {
@@ -153,11 +169,37 @@ impl Almanac {
             .unwrap()
     }
 
-    pub fn least_site_ranges(&self) -> usize {
+    // None if the seeds do not pair up into ranges.
+    pub fn least_site_ranges(&self) -> Option<usize> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return None;
+        }
         self.seeds
             .chunks(2)
             .map(|seed_chunk| self.seed_range_to_site(seed_chunk[0], seed_chunk[1]))
+            .collect::<Option<Vec<usize>>>()?
+            .into_iter()
             .min()
-            .unwrap()
+    }
+}
+
+impl fmt::Display for RangeMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} map:", self.name)?;
+        for (source, dest, len) in &self.map {
+            write!(f, "\n{} {} {}", dest, source, len)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Almanac {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "seeds: ")?;
+        join(f, &self.seeds, " ")?;
+        for map in &self.maps {
+            write!(f, "\n\n{}", map)?;
+        }
+        Ok(())
     }
 }
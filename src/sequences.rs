@@ -1,12 +1,20 @@
+use crate::answer::checked_sum_opt;
+use crate::utils::join;
 use itertools::Itertools;
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct SequenceGen {
     initial_state: Vec<i64>,
     length: usize,
 }
 
-fn delta(seq: &Vec<i64>) -> Vec<i64> {
-    seq.iter().tuple_windows().map(|(i, j)| j - i).collect()
+fn delta(seq: &[i64]) -> Option<Vec<i64>> {
+    seq.iter()
+        .tuple_windows()
+        .map(|(i, j)| j.checked_sub(*i))
+        .collect()
 }
 
 impl SequenceGen {
@@ -15,54 +23,59 @@ impl SequenceGen {
             .split(" ")
             .map(|part| part.parse::<i64>().ok())
             .collect::<Option<Vec<i64>>>()?;
-        Some(SequenceGen::from_sequence(&seq))
+        SequenceGen::from_sequence(&seq)
     }
 
-    fn from_sequence(sequence: &Vec<i64>) -> SequenceGen {
-        let first = sequence[0];
+    fn from_sequence(sequence: &[i64]) -> Option<SequenceGen> {
+        let first = *sequence.first()?;
         if sequence.iter().all(|v| *v == first) {
-            return SequenceGen {
+            return Some(SequenceGen {
                 initial_state: vec![first],
                 length: sequence.len(),
-            };
+            });
         }
-        let delta_sequence = delta(sequence);
-        let mut delta_gen = SequenceGen::from_sequence(&delta_sequence);
+        let delta_sequence = delta(sequence)?;
+        let mut delta_gen = SequenceGen::from_sequence(&delta_sequence)?;
         delta_gen.initial_state.insert(0, first);
         delta_gen.length += 1;
-        delta_gen
+        Some(delta_gen)
     }
 
-    fn next_val(&self) -> i64 {
+    fn next_val(&self) -> Option<i64> {
         self.get(self.length)
     }
 
-    fn get(&self, i: usize) -> i64 {
+    fn get(&self, i: usize) -> Option<i64> {
         let mut state = self.initial_state.clone();
-        for _ in 0..i {
-            for j in 0..(state.len() - 1) {
-                state[j] += state[j + 1]
+        // Only the differences that still feed into the value at i are moved
+        // on. Within the sequence those all came from the input, so only
+        // values past its end can overflow.
+        for step in 0..i {
+            for j in 0..(state.len() - 1).min(i - step) {
+                state[j] = state[j].checked_add(state[j + 1])?;
             }
         }
-        state[0]
+        Some(state[0])
     }
 
-    fn prev(&self) -> i64 {
-        let mut curr = 0;
+    fn prev(&self) -> Option<i64> {
+        let mut curr = 0_i64;
         let len = self.initial_state.len();
         for j in (0..len).rev() {
-            curr = self.initial_state[j] - curr;
+            curr = self.initial_state[j].checked_sub(curr)?;
         }
-        curr
+        Some(curr)
     }
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SequenceGens {
     gens: Vec<SequenceGen>,
 }
 
 impl SequenceGens {
-    pub fn from_lines(lines: &Vec<String>) -> Option<SequenceGens> {
+    pub fn from_lines(lines: &[String]) -> Option<SequenceGens> {
         let gens = lines
             .iter()
             .map(|line| SequenceGen::from_line(line))
@@ -70,11 +83,23 @@ impl SequenceGens {
         Some(SequenceGens { gens })
     }
 
-    pub fn next_sum(&self) -> i64 {
-        self.gens.iter().map(|gen| gen.next_val()).sum()
+    pub fn next_sum(&self) -> Option<i64> {
+        checked_sum_opt(self.gens.iter().map(|gen| gen.next_val()))
     }
 
-    pub fn prev_sum(&self) -> i64 {
-        self.gens.iter().map(|gen| gen.prev()).sum()
+    pub fn prev_sum(&self) -> Option<i64> {
+        checked_sum_opt(self.gens.iter().map(|gen| gen.prev()))
+    }
+}
+
+impl fmt::Display for SequenceGen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, (0..self.length).map_while(|i| self.get(i)), " ")
+    }
+}
+
+impl fmt::Display for SequenceGens {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.gens, "\n")
     }
 }
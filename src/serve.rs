@@ -0,0 +1,162 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use super::answer::{Answer, SolveError};
+use super::challenge;
+use super::control::Control;
+
+fn answer_json(answer: &Answer) -> Value {
+    match answer {
+        Answer::U32(val) => json!(val),
+        Answer::U64(val) => json!(val),
+        Answer::I64(val) => json!(val),
+        Answer::Text(val) => json!(val),
+    }
+}
+
+fn error_status(err: &SolveError) -> u16 {
+    match err {
+        SolveError::Parse => 422,
+        SolveError::Overflow => 422,
+        SolveError::NoSolver => 404,
+        SolveError::TimedOut => 504,
+        SolveError::Cancelled => 503,
+        SolveError::Panic => 500,
+        SolveError::Assumption => 422,
+        SolveError::NoAnswer => 422,
+    }
+}
+
+fn error_kind(err: &SolveError) -> &'static str {
+    match err {
+        SolveError::Parse => "parse",
+        SolveError::Overflow => "overflow",
+        SolveError::NoSolver => "no_solver",
+        SolveError::TimedOut => "timed_out",
+        SolveError::Cancelled => "cancelled",
+        SolveError::Panic => "panic",
+        SolveError::Assumption => "assumption",
+        SolveError::NoAnswer => "no_answer",
+    }
+}
+
+fn respond(request: Request, status: u16, body: Value) {
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    // The client may already have gone away, nothing useful to do about it.
+    let _ = request.respond(response);
+}
+
+fn error_body(kind: &str, message: &str) -> Value {
+    json!({ "error": kind, "message": message })
+}
+
+// Parses /solve/{day}/{part} into a day and part.
+fn route(url: &str) -> Option<(u8, u8)> {
+    let path = url.split('?').next()?;
+    let mut parts = path.strip_prefix("/solve/")?.split('/');
+    let day = parts.next()?.parse::<u8>().ok()?;
+    let part = parts.next()?.parse::<u8>().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((day, part))
+}
+
+fn handle(mut request: Request, timeout: Option<Duration>) {
+    let Some((day, part)) = route(request.url()) else {
+        respond(
+            request,
+            404,
+            error_body("not_found", "expected /solve/{day}/{part}"),
+        );
+        return;
+    };
+    if *request.method() != Method::Post {
+        respond(
+            request,
+            405,
+            error_body("method", "use POST with the input as the body"),
+        );
+        return;
+    }
+    let mut body = String::new();
+    if request.as_reader().read_to_string(&mut body).is_err() {
+        respond(request, 400, error_body("input", "body is not valid UTF-8"));
+        return;
+    }
+    let mut control = Control::new();
+    if let Some(timeout) = timeout {
+        control = control.with_timeout(timeout);
+    }
+    let start = Instant::now();
+    let result = challenge::solve(day, part, &body, &control);
+    let time_ms = start.elapsed().as_secs_f64() * 1000.0;
+    match result {
+        Ok(answer) => respond(
+            request,
+            200,
+            json!({
+                "day": day,
+                "part": part,
+                "answer": answer_json(&answer),
+                "time_ms": time_ms,
+            }),
+        ),
+        Err(err) => respond(
+            request,
+            error_status(&err),
+            error_body(error_kind(&err), &err.to_string()),
+        ),
+    }
+}
+
+// One of the limited number of requests being solved at once, given back
+// when its thread is done with it.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(busy: &Arc<AtomicUsize>, workers: usize) -> Option<Slot> {
+        busy.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |taken| {
+            (taken < workers).then_some(taken + 1)
+        })
+        .ok()?;
+        Some(Slot(busy.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Solves at most workers requests at once, each on its own thread. Requests
+// arriving while all are taken get a 503 instead of another thread.
+pub fn serve(port: u16, timeout: Option<Duration>, workers: usize) -> Result<(), String> {
+    let server = Server::http(("127.0.0.1", port)).map_err(|err| err.to_string())?;
+    eprintln!("Listening on http://127.0.0.1:{}", port);
+    let busy = Arc::new(AtomicUsize::new(0));
+    for request in server.incoming_requests() {
+        let Some(slot) = Slot::take(&busy, workers) else {
+            respond(
+                request,
+                503,
+                error_body("busy", "every worker is solving, try again later"),
+            );
+            continue;
+        };
+        thread::spawn(move || {
+            handle(request, timeout);
+            drop(slot);
+        });
+    }
+    Ok(())
+}
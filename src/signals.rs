@@ -1,22 +1,42 @@
+use crate::answer::SolveError;
+use crate::assumption::Assumption;
+use crate::control::Control;
+use crate::simulation::Simulation;
+use num::Integer;
 use std::collections::{HashMap, VecDeque};
+use std::fmt;
 
+// How many pulses a single press may deliver before the network is taken
+// never to settle.
+const MAX_PULSES: usize = 1 << 20;
+
+// How many presses checking the period assumption simulates before giving up
+// on the conjunction's inputs ever settling into periods.
+const PERIOD_LIMIT: usize = 1 << 16;
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum State {
     Broadcast,
     FlipFlop(bool),
     Conjunction(HashMap<String, bool>),
 }
 
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Module {
     state: State,
     outputs: Vec<String>,
 }
 
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Network {
     modules: HashMap<String, Module>,
 }
 
 impl Network {
-    pub fn from_lines(lines: &Vec<String>) -> Option<Network> {
+    pub fn from_lines(lines: &[String]) -> Option<Network> {
         let modules = lines
             .iter()
             .map(|line| Module::from_line(line))
@@ -26,51 +46,159 @@ impl Network {
         Some(network)
     }
 
-    pub fn count_signals(&mut self, num: usize) -> usize {
+    pub fn count_signals(&mut self, num: usize) -> Result<usize, SolveError> {
         let mut count_low: usize = 0;
         let mut count_high: usize = 0;
         for _ in 0..num {
-            let (l, h, _) = self.count_signal();
-            count_high += h;
-            count_low += l;
+            let (l, h, _) = self.count_signal()?;
+            count_high = count_high.checked_add(h).ok_or(SolveError::Overflow)?;
+            count_low = count_low.checked_add(l).ok_or(SolveError::Overflow)?;
         }
-        count_low * count_high
+        count_low
+            .checked_mul(count_high)
+            .ok_or(SolveError::Overflow)
     }
 
-    pub fn count_presses(&mut self) -> usize {
+    // Fails if cancelled before rx received a low pulse.
+    pub fn count_presses(&mut self, control: &Control) -> Result<usize, SolveError> {
         for i in 0_usize.. {
             if i % 1024 == 0 {
-                println!("{}", i);
+                control.tick(i, None).ok_or(control.error())?;
             }
-            let (_, _, fin) = self.count_signal();
+            let (_, _, fin) = self.count_signal()?;
             if fin {
-                return i + 1;
+                return Ok(i + 1);
+            }
+        }
+        Ok(0)
+    }
+
+    // rx is fed by a single conjunction, which sends rx a low pulse once all
+    // of its inputs last sent it a high one. Each input does so on a fixed
+    // period from the first press, so rx first gets a low pulse at the lcm
+    // of those periods.
+    pub fn count_presses_period(&mut self, control: &Control) -> Result<usize, SolveError> {
+        self.feeder_periods(None, control)?
+            .iter()
+            .try_fold(1_usize, |a, b| (a / a.gcd(b)).checked_mul(*b))
+            .ok_or(SolveError::Overflow)
+    }
+
+    // The press at which each input of the rx feeder first sends it a high
+    // pulse. An input must next do so at twice that press and not before, or
+    // its highs are not a period counted from the first press and the lcm
+    // means nothing. Fails with Assumption if one does not, or if the inputs
+    // have not all shown their period within the limit on presses.
+    fn feeder_periods(
+        &mut self,
+        limit: Option<usize>,
+        control: &Control,
+    ) -> Result<Vec<usize>, SolveError> {
+        let (feeder, inputs) = self.rx_feeder().ok_or(SolveError::Assumption)?;
+        let feeder = feeder.to_string();
+        let mut highs: HashMap<String, Vec<usize>> =
+            inputs.into_iter().map(|k| (k, Vec::new())).collect();
+        for i in 1_usize.. {
+            if i % 1024 == 0 {
+                control.tick(i, None).ok_or(control.error())?;
+            }
+            if limit.is_some_and(|limit| i > limit) {
+                return Err(SolveError::Assumption);
+            }
+            self.press(|from, to, pulse| {
+                if to == feeder && pulse {
+                    if let Some(presses) = highs.get_mut(from).filter(|p| p.last() != Some(&i)) {
+                        presses.push(i);
+                    }
+                }
+            })?;
+            if highs.values().all(|presses| presses.len() >= 2) {
+                break;
             }
         }
-        0
+        highs
+            .values()
+            .map(|presses| match presses[..] {
+                [first, second, ..] if second == 2 * first => Ok(first),
+                _ => Err(SolveError::Assumption),
+            })
+            .collect()
+    }
+
+    // The single conjunction sending to rx and its inputs.
+    fn rx_feeder(&self) -> Option<(&str, Vec<String>)> {
+        let feeders: Vec<&String> = self
+            .modules
+            .iter()
+            .filter(|(_, module)| module.outputs.iter().any(|out| out == "rx"))
+            .map(|(name, _)| name)
+            .collect();
+        let [feeder] = feeders[..] else {
+            return None;
+        };
+        let State::Conjunction(inputs) = &self.modules.get(feeder)?.state else {
+            return None;
+        };
+        if inputs.is_empty() {
+            return None;
+        }
+        Some((feeder, inputs.keys().cloned().collect()))
+    }
+
+    pub fn period_assumptions(&self) -> Vec<Assumption> {
+        let cycles = self
+            .clone()
+            .feeder_periods(Some(PERIOD_LIMIT), &Control::new())
+            .is_ok();
+        vec![
+            Assumption::new(
+                "rx is fed by a single conjunction (period)",
+                self.rx_feeder().is_some(),
+            ),
+            Assumption::new(
+                "each of its inputs sends it a high pulse on a period from the first press (period)",
+                cycles,
+            ),
+        ]
     }
 
-    fn count_signal(&mut self) -> (usize, usize, bool) {
-        let mut count_low: usize = 1;
+    fn count_signal(&mut self) -> Result<(usize, usize, bool), SolveError> {
+        let mut count_low: usize = 0;
         let mut count_high: usize = 0;
         let mut fin = false;
+        self.press(|_, to, pulse| {
+            if pulse {
+                count_high += 1;
+            } else {
+                count_low += 1;
+            }
+            if to == "rx" && !pulse {
+                fin = true;
+            }
+        })?;
+        Ok((count_low, count_high, fin))
+    }
+
+    // Presses the button once, calling observe with every pulse delivered.
+    // Fails with NoAnswer if the pulses never die down, as when a module
+    // feeds itself.
+    fn press(&mut self, mut observe: impl FnMut(&str, &str, bool)) -> Result<(), SolveError> {
         let mut to_send: VecDeque<(String, String, bool)> = VecDeque::new();
         to_send.push_back(("button".to_string(), "broadcaster".to_string(), false));
-        while !to_send.is_empty() {
-            let (from, module_name, pulse) = to_send.pop_front().unwrap();
-            if module_name == "rx" && !pulse {
-                fin = true;
+        let mut delivered = 0;
+        while let Some((from, module_name, pulse)) = to_send.pop_front() {
+            delivered += 1;
+            if delivered > MAX_PULSES {
+                return Err(SolveError::NoAnswer);
             }
+            observe(&from, &module_name, pulse);
             if let Some(module) = self.modules.get_mut(&module_name) {
-                let outputs = module.send(&from, pulse);
-                count_low += outputs.iter().filter(|(_, pulse)| !*pulse).count();
-                count_high += outputs.iter().filter(|(_, pulse)| *pulse).count();
-                for output in outputs {
+                for output in module.send(&from, pulse) {
                     to_send.push_back((module_name.clone(), output.0, output.1))
                 }
             }
         }
-        (count_low, count_high, fin)
+        Ok(())
     }
 
     fn set_con_inputs(&mut self) {
@@ -84,8 +212,8 @@ impl Network {
             }
         }
         for (name, module) in self.modules.iter_mut() {
-            if let State::Conjunction(map) = &mut module.state {
-                for input in inputs.get(name).unwrap() {
+            if let (State::Conjunction(map), Some(inputs)) = (&mut module.state, inputs.get(name)) {
+                for input in inputs {
                     map.insert(input.to_string(), false);
                 }
             }
@@ -93,6 +221,47 @@ impl Network {
     }
 }
 
+// A tick is one press of the button.
+impl Simulation for Network {
+    type Snapshot = Network;
+
+    // A press that never settles ends the simulation.
+    fn step(&mut self) -> bool {
+        self.press(|_, _, _| {}).is_ok()
+    }
+
+    fn snapshot(&self) -> Network {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: &Network) {
+        *self = snapshot.clone();
+    }
+
+    fn state(&self) -> String {
+        let mut names: Vec<&String> = self.modules.keys().collect();
+        names.sort();
+        names
+            .iter()
+            .map(|name| match &self.modules[*name].state {
+                State::Broadcast => name.to_string(),
+                State::FlipFlop(on) => format!("%{} {}", name, if *on { "on" } else { "off" }),
+                State::Conjunction(inputs) => {
+                    let mut inputs: Vec<String> = inputs
+                        .iter()
+                        .map(|(input, high)| {
+                            format!("{}={}", input, if *high { "high" } else { "low" })
+                        })
+                        .collect();
+                    inputs.sort();
+                    format!("&{} {}", name, inputs.join(" "))
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
 impl Module {
     fn from_line(line: &str) -> Option<(String, Module)> {
         let (name_dec, output_str) = line.split_once(" -> ")?;
@@ -113,16 +282,16 @@ impl Module {
                     outputs,
                 },
             ))
-        } else if let Some(name) = name_dec.strip_prefix("&") {
-            Some((
-                name.to_string(),
-                Module {
-                    state: State::Conjunction(HashMap::new()),
-                    outputs,
-                },
-            ))
         } else {
-            None
+            name_dec.strip_prefix("&").map(|name| {
+                (
+                    name.to_string(),
+                    Module {
+                        state: State::Conjunction(HashMap::new()),
+                        outputs,
+                    },
+                )
+            })
         }
     }
 
@@ -157,3 +326,79 @@ impl State {
         }
     }
 }
+
+// The modules are written by name, with their state left out as the puzzle
+// text always starts them off.
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names: Vec<&String> = self.modules.keys().collect();
+        names.sort();
+        for (i, name) in names.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let module = &self.modules[name];
+            let prefix = match module.state {
+                State::Broadcast => "",
+                State::FlipFlop(_) => "%",
+                State::Conjunction(_) => "&",
+            };
+            write!(f, "{}{} -> {}", prefix, name, module.outputs.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(text: &str) -> Network {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        Network::from_lines(&lines).unwrap()
+    }
+
+    #[test]
+    fn a_module_feeding_itself_has_no_answer() {
+        let mut looping = network("broadcaster -> broadcaster");
+        assert_eq!(looping.count_signals(1000), Err(SolveError::NoAnswer));
+        let mut looping = network("broadcaster -> broadcaster");
+        assert_eq!(
+            looping.count_presses(&Control::new()),
+            Err(SolveError::NoAnswer)
+        );
+    }
+
+    #[test]
+    fn counts_the_pulses_of_a_settling_network() {
+        let mut settling =
+            network("broadcaster -> a, b, c\n%a -> b\n%b -> c\n%c -> inv\n&inv -> a");
+        assert_eq!(settling.count_signals(1000), Ok(32000000));
+    }
+
+    fn holds(text: &str) -> Vec<bool> {
+        let assumptions = network(text).period_assumptions();
+        assumptions
+            .iter()
+            .map(|assumption| assumption.holds)
+            .collect()
+    }
+
+    // The inverter sends hub a high pulse every second press.
+    #[test]
+    fn periodic_inputs_to_the_rx_feeder_hold() {
+        let periodic = "broadcaster -> a\n%a -> inv\n&inv -> hub\n&hub -> rx";
+        assert_eq!(holds(periodic), [true, true]);
+    }
+
+    // The flip-flop sends hub a high pulse on presses 1, 3, 5 and so on,
+    // which is a period counted from press 1 rather than from nothing.
+    #[test]
+    fn offset_inputs_to_the_rx_feeder_do_not() {
+        assert_eq!(
+            holds("broadcaster -> a\n%a -> hub\n&hub -> rx"),
+            [true, false]
+        );
+        assert_eq!(holds("broadcaster -> a\n%a -> rx"), [false, false]);
+    }
+}
@@ -0,0 +1,254 @@
+use super::answer::SolveError;
+use super::*;
+
+// A model that advances a tick at a time, such as the tilting platform or
+// the pulse network.
+pub trait Simulation {
+    type Snapshot;
+
+    // Advances one tick, false once there is nothing left to change.
+    fn step(&mut self) -> bool;
+    fn snapshot(&self) -> Self::Snapshot;
+    fn restore(&mut self, snapshot: &Self::Snapshot);
+    // The current state, a line per part of the model so ticks can be diffed.
+    fn state(&self) -> String;
+}
+
+// How many ticks apart the debugger keeps snapshots.
+const CHECKPOINT: usize = 64;
+
+// A simulation with a snapshot taken every CHECKPOINT ticks so far, so it can
+// be rewound as well as stepped. Rewinding restores the last snapshot before
+// the tick and steps forward from there.
+pub struct Debugger<S: Simulation> {
+    simulation: S,
+    checkpoints: Vec<S::Snapshot>,
+    tick: usize,
+    // The tick at which a step first changed nothing.
+    last: Option<usize>,
+}
+
+// What the debug command drives, whichever model is behind it.
+pub trait Stepper {
+    fn tick(&self) -> usize;
+    fn state(&self) -> String;
+    // False if the simulation has already finished.
+    fn forward(&mut self) -> bool;
+    // False if already at the start.
+    fn back(&mut self) -> bool;
+    // Steps forward or rewinds to the given tick, stopping early if the
+    // simulation finishes first.
+    fn jump(&mut self, tick: usize);
+}
+
+impl<S: Simulation> Debugger<S> {
+    pub fn new(simulation: S) -> Debugger<S> {
+        let checkpoints = vec![simulation.snapshot()];
+        Debugger {
+            simulation,
+            checkpoints,
+            tick: 0,
+            last: None,
+        }
+    }
+
+    // Goes back to an earlier tick, or the current one.
+    fn rewind(&mut self, tick: usize) {
+        let checkpoint = tick / CHECKPOINT;
+        self.simulation.restore(&self.checkpoints[checkpoint]);
+        self.tick = checkpoint * CHECKPOINT;
+        while self.tick < tick {
+            self.simulation.step();
+            self.tick += 1;
+        }
+    }
+}
+
+impl<S: Simulation> Stepper for Debugger<S> {
+    fn tick(&self) -> usize {
+        self.tick
+    }
+
+    fn state(&self) -> String {
+        self.simulation.state()
+    }
+
+    fn forward(&mut self) -> bool {
+        if self.last == Some(self.tick) {
+            return false;
+        }
+        if !self.simulation.step() {
+            self.last = Some(self.tick);
+            return false;
+        }
+        self.tick += 1;
+        if self.tick == self.checkpoints.len() * CHECKPOINT {
+            self.checkpoints.push(self.simulation.snapshot());
+        }
+        true
+    }
+
+    fn back(&mut self) -> bool {
+        if self.tick == 0 {
+            return false;
+        }
+        self.rewind(self.tick - 1);
+        true
+    }
+
+    fn jump(&mut self, tick: usize) {
+        if tick < self.tick {
+            self.rewind(tick);
+        }
+        while self.tick < tick && self.forward() {}
+    }
+}
+
+// The numbered lines that differ between two states, the old line marked -
+// and the new one +.
+pub fn changes(before: &str, after: &str) -> Vec<String> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let mut changes = Vec::new();
+    for i in 0..before.len().max(after.len()) {
+        match (before.get(i), after.get(i)) {
+            (Some(old), Some(new)) if old == new => {}
+            (old, new) => {
+                if let Some(old) = old {
+                    changes.push(format!("{:>4} - {}", i + 1, old));
+                }
+                if let Some(new) = new {
+                    changes.push(format!("{:>4} + {}", i + 1, new));
+                }
+            }
+        }
+    }
+    changes
+}
+
+// Runs a debugger over the simulation for a day, for the days that have one.
+// The debugger lasts only for the call, as a simulation may borrow the model
+// it runs over.
+pub fn with_debugger<R>(
+    day: u8,
+    input: &str,
+    run: impl FnOnce(&mut dyn Stepper) -> R,
+) -> Result<R, SolveError> {
+    let input = io::normalize(input);
+    match day {
+        14 => Ok(run(&mut Debugger::new(beams::Beam::from_grid(&io::grid(
+            &input,
+        ))))),
+        16 => {
+            let cave = mirrors::MirrorCave::from_grid(&io::grid(&input));
+            Ok(run(&mut Debugger::new(cave.lighting())))
+        }
+        20 => {
+            let network =
+                signals::Network::from_lines(&io::lines(&input)).ok_or(SolveError::Parse)?;
+            Ok(run(&mut Debugger::new(network)))
+        }
+        22 => {
+            let bricks = bricks::Bricks::from_lines(&io::lines(&input)).ok_or(SolveError::Parse)?;
+            Ok(run(&mut Debugger::new(bricks)))
+        }
+        _ => Err(SolveError::NoSolver),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Counts up to a limit, so the state says which tick it is on.
+    struct Counter {
+        count: usize,
+        limit: usize,
+    }
+
+    impl Simulation for Counter {
+        type Snapshot = usize;
+
+        fn step(&mut self) -> bool {
+            if self.count == self.limit {
+                return false;
+            }
+            self.count += 1;
+            true
+        }
+
+        fn snapshot(&self) -> usize {
+            self.count
+        }
+
+        fn restore(&mut self, snapshot: &usize) {
+            self.count = *snapshot;
+        }
+
+        fn state(&self) -> String {
+            self.count.to_string()
+        }
+    }
+
+    fn counter(limit: usize) -> Debugger<Counter> {
+        Debugger::new(Counter { count: 0, limit })
+    }
+
+    #[test]
+    fn keeps_a_snapshot_every_checkpoint() {
+        let mut debugger = counter(1000);
+        debugger.jump(2 * CHECKPOINT + 5);
+        assert_eq!(debugger.checkpoints, vec![0, CHECKPOINT, 2 * CHECKPOINT]);
+        debugger.jump(3);
+        debugger.jump(2 * CHECKPOINT + 5);
+        assert_eq!(debugger.checkpoints.len(), 3);
+    }
+
+    #[test]
+    fn rewinds_across_checkpoints() {
+        let mut debugger = counter(1000);
+        debugger.jump(2 * CHECKPOINT + 5);
+        for tick in [CHECKPOINT + 1, CHECKPOINT, CHECKPOINT - 1, 0] {
+            debugger.jump(tick);
+            assert_eq!(debugger.tick(), tick);
+            assert_eq!(debugger.state(), tick.to_string());
+        }
+        assert!(!debugger.back());
+        debugger.jump(CHECKPOINT);
+        assert!(debugger.back());
+        assert_eq!(debugger.state(), (CHECKPOINT - 1).to_string());
+        assert!(debugger.forward());
+        assert_eq!(debugger.state(), CHECKPOINT.to_string());
+    }
+
+    #[test]
+    fn stops_at_the_end_however_it_gets_there() {
+        let mut debugger = counter(CHECKPOINT + 10);
+        debugger.jump(1000);
+        assert_eq!(debugger.tick(), CHECKPOINT + 10);
+        assert!(!debugger.forward());
+        debugger.jump(3);
+        debugger.jump(1000);
+        assert_eq!(debugger.tick(), CHECKPOINT + 10);
+        assert_eq!(debugger.state(), (CHECKPOINT + 10).to_string());
+    }
+
+    #[test]
+    fn a_rewound_beam_matches_a_fresh_one() {
+        let cave = mirrors::MirrorCave::from_grid(&io::grid(
+            ".|...\\....\n|.-.\\.....\n.....|-...\n........|.\n..........\n\
+             .........\\\n..../.\\\\..\n.-.-/..|..\n.|....-|.\\\n..//.|....",
+        ));
+        let fresh = |tick: usize| {
+            let mut debugger = Debugger::new(cave.lighting());
+            debugger.jump(tick);
+            debugger.state()
+        };
+        let mut debugger = Debugger::new(cave.lighting());
+        debugger.jump(1000);
+        let last = debugger.tick();
+        assert_eq!(debugger.state(), fresh(last));
+        debugger.jump(last / 2);
+        assert_eq!(debugger.state(), fresh(last / 2));
+    }
+}
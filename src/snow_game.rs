@@ -1,116 +1,526 @@
+use crate::answer::{checked_product, checked_sum, checked_sum_opt, SolveError};
+use crate::control::Control;
+use crate::utils::join;
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::fmt;
 
+// The share of the posterior a credible range covers.
+const CREDIBLE: f64 = 0.9;
+
+// How far in ln the posterior weight of a total may fall below the heaviest
+// before larger totals are left out of an estimate.
+const TAIL: f64 = 20.0;
+
+// The most cubes an estimated bag may hold, which bounds the work.
+pub const MAX_CUBES: u32 = 1000;
+
+// The colours of the puzzle's own bag, which the power of a game is taken over.
+const PUZZLE_COLOURS: [&str; 3] = ["red", "green", "blue"];
+
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Round {
-    red: Option<u32>,
-    green: Option<u32>,
-    blue: Option<u32>,
+    cubes: BTreeMap<String, u32>,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Game {
+    id: u32,
     rounds: Vec<Round>,
 }
 
+// The games in the order they were listed.
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Games {
-    games: HashMap<u32, Game>,
+    games: Vec<Game>,
+}
+
+// What to make of a round showing a colour the bag has no limit for.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownColour {
+    // The game cannot be judged, so neither can the sum.
+    #[default]
+    Error,
+    // Only the colours the bag knows about are checked.
+    Ignore,
+    // The bag holds none of it, so any showing makes the round impossible.
+    Zero,
 }
 
-fn max_merge_val(o1: &Option<u32>, o2: &Option<u32>) -> Option<u32> {
-    if let Some(v1) = o1 {
-        if let Some(v2) = o2 {
-            Some(max(*v1, *v2))
-        } else {
-            Some(*v1)
+// The most cubes of each colour the bag holds.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
+pub struct Bag {
+    limits: BTreeMap<String, u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    unknown: UnknownColour,
+}
+
+// The first round of a game to show more cubes of a colour than the bag
+// holds, numbered from 1.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Violation {
+    pub round: usize,
+    pub colour: String,
+    pub count: u32,
+    pub limit: u32,
+}
+
+// A game checked against a bag.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GameReport {
+    pub id: u32,
+    pub rounds: usize,
+    pub minimal: Bag,
+    // Taken over red, green and blue, None if it overflows.
+    pub power: Option<u64>,
+    // None if the game is possible with the bag.
+    pub violation: Option<Violation>,
+}
+
+// The most probable contents of the bag behind a game, taking each round as
+// cubes drawn together without replacement and put back before the next.
+pub struct Estimate {
+    pub bag: Bag,
+    // ln of the chance of the game from the bag.
+    pub log_likelihood: f64,
+    // The central 90% of each colour's count under the posterior.
+    pub ranges: BTreeMap<String, (u32, u32)>,
+    // Whether the bound on total cubes cut off bags that still carried
+    // weight, so a larger one could make the estimate different.
+    pub bounded: bool,
+}
+
+impl UnknownColour {
+    pub fn from_string(string: &str) -> Option<UnknownColour> {
+        match string {
+            "error" => Some(UnknownColour::Error),
+            "ignore" => Some(UnknownColour::Ignore),
+            "zero" => Some(UnknownColour::Zero),
+            _ => None,
         }
-    } else {
-        if let Some(v2) = o2 {
-            Some(*v2)
-        } else {
-            None
+    }
+}
+
+impl Bag {
+    // An empty bag, rejecting any colour until limits are added.
+    pub fn new() -> Bag {
+        Bag {
+            limits: BTreeMap::new(),
+            unknown: UnknownColour::Error,
         }
     }
+
+    // 12 red, 13 green and 14 blue cubes, as in the puzzle.
+    pub fn puzzle() -> Bag {
+        Bag::new()
+            .with("red", 12)
+            .with("green", 13)
+            .with("blue", 14)
+    }
+
+    // The limits written like a round, e.g. "12 red, 13 green, 14 blue".
+    pub fn from_string(string: &str) -> Option<Bag> {
+        let round = Round::from_string(string)?;
+        Some(Bag {
+            limits: round.cubes,
+            unknown: UnknownColour::Error,
+        })
+    }
+
+    pub fn with(mut self, colour: &str, limit: u32) -> Bag {
+        self.limits.insert(colour.to_string(), limit);
+        self
+    }
+
+    pub fn with_unknown(mut self, unknown: UnknownColour) -> Bag {
+        self.unknown = unknown;
+        self
+    }
+
+    // How many cubes the bag holds altogether.
+    pub fn total(&self) -> Option<u64> {
+        checked_sum(self.limits.values().map(|limit| *limit as u64))
+    }
+
+    // None for a colour the bag does not know about under the error policy.
+    fn limit(&self, colour: &str) -> Option<Option<u32>> {
+        match (self.limits.get(colour), self.unknown) {
+            (Some(limit), _) => Some(Some(*limit)),
+            (None, UnknownColour::Error) => None,
+            (None, UnknownColour::Ignore) => Some(None),
+            (None, UnknownColour::Zero) => Some(Some(0)),
+        }
+    }
+}
+
+impl Default for Bag {
+    fn default() -> Bag {
+        Bag::new()
+    }
 }
 
 impl Round {
     fn empty() -> Round {
         Round {
-            red: None,
-            green: None,
-            blue: None,
+            cubes: BTreeMap::new(),
         }
     }
+
     fn from_string(string: &str) -> Option<Round> {
         let mut round = Round::empty();
         for part in string.split(", ") {
-            let (value_str, name_str) = part.split_once(' ')?;
+            let (value_str, colour) = part.split_once(' ')?;
             let value = value_str.parse::<u32>().ok()?;
-            match name_str {
-                "red" => round.add_red(value),
-                "blue" => round.add_blue(value),
-                "green" => round.add_green(value),
-                _ => return None,
+            if colour.is_empty() {
+                return None;
             }
+            round.add(colour, value)?;
         }
         Some(round)
     }
 
-    fn add_red(&mut self, val: u32) {
-        *self.red.get_or_insert(0) += val;
-    }
-
-    fn add_blue(&mut self, val: u32) {
-        *self.blue.get_or_insert(0) += val;
+    fn add(&mut self, colour: &str, val: u32) -> Option<()> {
+        let count = self.cubes.entry(colour.to_string()).or_insert(0);
+        *count = count.checked_add(val)?;
+        Some(())
     }
 
-    fn add_green(&mut self, val: u32) {
-        *self.green.get_or_insert(0) += val;
+    fn count(&self, colour: &str) -> u32 {
+        self.cubes.get(colour).copied().unwrap_or(0)
     }
 
-    fn possible(&self) -> bool {
-        if let Some(r) = self.red {
-            if r > 12 {
-                return false;
+    // The first colour with more cubes than the bag holds, with the count
+    // and limit. None if the round shows a colour the bag rejects.
+    fn violation(&self, bag: &Bag) -> Option<Option<(&String, u32, u32)>> {
+        let mut violation = None;
+        for (colour, count) in &self.cubes {
+            match bag.limit(colour)? {
+                Some(limit) if *count > limit && violation.is_none() => {
+                    violation = Some((colour, *count, limit))
+                }
+                _ => {}
             }
         }
-        if let Some(g) = self.green {
-            if g > 13 {
-                return false;
-            }
-        }
-        if let Some(b) = self.blue {
-            if b > 14 {
-                return false;
-            }
+        Some(violation)
+    }
+
+    fn into_bag(self) -> Bag {
+        Bag {
+            limits: self.cubes,
+            unknown: UnknownColour::Error,
         }
-        true
     }
 
     fn max_merge(&self, other: &Round) -> Round {
-        Round {
-            red: max_merge_val(&self.red, &other.red),
-            green: max_merge_val(&self.green, &other.green),
-            blue: max_merge_val(&self.blue, &other.blue),
+        let mut cubes = self.cubes.clone();
+        for (colour, count) in &other.cubes {
+            let merged = cubes.entry(colour.clone()).or_insert(0);
+            *merged = max(*merged, *count);
+        }
+        Round { cubes }
+    }
+
+    fn power_set(&self) -> Option<u64> {
+        checked_product(PUZZLE_COLOURS.map(|colour| self.count(colour) as u64))
+    }
+}
+
+// ln of n choose k, multiplied out.
+fn ln_choose(n: u32, k: u32) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    (0..k.min(n - k))
+        .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+        .sum()
+}
+
+// ln of the sum of the exponentials of the terms.
+fn ln_sum(terms: impl Iterator<Item = f64> + Clone) -> f64 {
+    let max = terms.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + terms.map(|term| (term - max).exp()).sum::<f64>().ln()
+}
+
+// Entry n of the convolution of two sequences, all in ln.
+fn ln_convolve_at(a: &[f64], b: &[f64], n: usize) -> f64 {
+    ln_sum((0..=n).map(|i| a[i] + b[n - i]))
+}
+
+// The central CREDIBLE share of a distribution over counts given as ln of
+// their weights, which need not be normalised.
+fn credible(ln_weights: &[f64]) -> (u32, u32) {
+    let tail = (1.0 - CREDIBLE) / 2.0;
+    let ln_norm = ln_sum(ln_weights.iter().copied());
+    let mut seen = 0.0;
+    let (mut low, mut high) = (None, ln_weights.len().saturating_sub(1) as u32);
+    for (count, weight) in ln_weights.iter().enumerate() {
+        seen += (weight - ln_norm).exp();
+        if low.is_none() && seen >= tail {
+            low = Some(count as u32);
         }
+        if seen >= 1.0 - tail {
+            high = count as u32;
+            break;
+        }
+    }
+    (low.unwrap_or(0), high)
+}
+
+// The rounds of a game as counts lined up with a list of colours, with the
+// number of cubes drawn in each.
+struct Draws {
+    rounds: Vec<(Vec<u32>, u64)>,
+}
+
+impl Draws {
+    fn new(game: &Game, colours: &[&String]) -> Draws {
+        let rounds = game
+            .rounds
+            .iter()
+            .map(|round| {
+                let counts: Vec<u32> = colours.iter().map(|colour| round.count(colour)).collect();
+                let drawn = round.cubes.values().map(|count| *count as u64).sum();
+                (counts, drawn)
+            })
+            .collect();
+        Draws { rounds }
+    }
+
+    // ln of the ways every round could have drawn its cubes of the colour
+    // from count of them.
+    fn ln_colour(&self, colour: usize, count: u32) -> f64 {
+        self.rounds
+            .iter()
+            .map(|(counts, _)| ln_choose(count, counts[colour]))
+            .sum()
+    }
+
+    // ln of the ways every round could have drawn its cubes from total.
+    fn ln_total(&self, total: u32) -> f64 {
+        self.rounds
+            .iter()
+            .map(|(_, drawn)| match u32::try_from(*drawn) {
+                Ok(drawn) => ln_choose(total, drawn),
+                Err(_) => f64::NEG_INFINITY,
+            })
+            .sum()
     }
 
-    fn power_set(&self) -> u64 {
-        self.red.unwrap_or(0) as u64
-            * self.green.unwrap_or(0) as u64
-            * self.blue.unwrap_or(0) as u64
+    // ln of the chance of every round coming out as it did from a bag with
+    // the given counts, which sum to total.
+    fn log_likelihood(&self, counts: &[u32], total: u32) -> f64 {
+        let ln_colours: f64 = counts
+            .iter()
+            .enumerate()
+            .map(|(colour, count)| self.ln_colour(colour, *count))
+            .sum();
+        // A bag short of a colour is also short of cubes, which would leave
+        // infinity less infinity.
+        if ln_colours == f64::NEG_INFINITY {
+            return ln_colours;
+        }
+        ln_colours - self.ln_total(total)
     }
 }
 
 impl Game {
-    fn from_string(string: &str) -> Option<Game> {
+    fn from_string(id: u32, string: &str) -> Option<Game> {
         let rounds = string
             .split("; ")
-            .map(|part| Round::from_string(part))
+            .map(Round::from_string)
             .collect::<Option<Vec<Round>>>()?;
-        Some(Game { rounds })
+        Some(Game { id, rounds })
+    }
+
+    // The first round the bag could not have given. None if any round shows
+    // a colour the bag rejects.
+    fn violation(&self, bag: &Bag) -> Option<Option<Violation>> {
+        let mut violation = None;
+        for (i, round) in self.rounds.iter().enumerate() {
+            if let (Some((colour, count, limit)), None) = (round.violation(bag)?, &violation) {
+                violation = Some(Violation {
+                    round: i + 1,
+                    colour: colour.clone(),
+                    count,
+                    limit,
+                });
+            }
+        }
+        Some(violation)
+    }
+
+    fn possible(&self, bag: &Bag) -> Option<bool> {
+        Some(self.violation(bag)?.is_none())
     }
 
-    fn possible(&self) -> bool {
-        self.rounds.iter().all(|round| round.possible())
+    fn report(&self, bag: &Bag) -> Option<GameReport> {
+        let minimal = self.max_merge_round();
+        Some(GameReport {
+            id: self.id,
+            rounds: self.rounds.len(),
+            power: minimal.power_set(),
+            minimal: minimal.into_bag(),
+            violation: self.violation(bag)?,
+        })
+    }
+
+    // ln of the chance of the game from the bag, with no cubes at all of a
+    // colour the bag has no limit for.
+    fn log_likelihood(&self, bag: &Bag) -> Option<f64> {
+        let minimal = self.max_merge_round();
+        let unknown = minimal
+            .cubes
+            .keys()
+            .filter(|colour| !bag.limits.contains_key(*colour));
+        let colours: Vec<&String> = bag.limits.keys().chain(unknown).collect();
+        let counts: Vec<u32> = colours
+            .iter()
+            .map(|colour| bag.limits.get(*colour).copied().unwrap_or(0))
+            .collect();
+        let total = checked_sum(counts.iter().copied())?;
+        let draws = Draws::new(self, &colours);
+        Some(draws.log_likelihood(&counts, total))
+    }
+
+    // The most probable bag of the colours seen, taking each colour's count
+    // before the game as geometric with the prior mean. Other colours are
+    // left out, as any cubes of them only make the draws less likely. Totals
+    // are tried upwards until the posterior weight of a total falls TAIL
+    // below the heaviest, or max_cubes (at most MAX_CUBES) is reached. None
+    // if no bag that small could have been used.
+    fn estimate(
+        &self,
+        prior_mean: f64,
+        max_cubes: u32,
+        control: &Control,
+    ) -> Result<Option<Estimate>, SolveError> {
+        let max_cubes = max_cubes.min(MAX_CUBES);
+        let minimal = self.max_merge_round();
+        let colours: Vec<&String> = minimal.cubes.keys().collect();
+        let mut counts: Vec<u32> = minimal.cubes.values().copied().collect();
+        let least = match checked_sum(counts.iter().copied()) {
+            Some(least) if least <= max_cubes => least,
+            _ => return Ok(None),
+        };
+        let draws = Draws::new(self, &colours);
+        let ln_prior = (prior_mean / (prior_mean + 1.0)).ln();
+        // By count, ln of the weight of each colour: its draws and its prior.
+        let mut weights = vec![Vec::new(); colours.len()];
+        // By total, ln of the summed weight of every bag of the first i
+        // colours, starting from the empty bag.
+        let mut sums = vec![Vec::new(); colours.len() + 1];
+        let mut ln_totals = Vec::new();
+        let mut best: Option<(f64, Vec<u32>)> = None;
+        let mut heaviest = f64::NEG_INFINITY;
+        let mut bounded = true;
+        for total in 0..=max_cubes {
+            control
+                .tick(total as usize, Some(max_cubes as usize))
+                .ok_or(control.error())?;
+            let n = total as usize;
+            for (colour, weights) in weights.iter_mut().enumerate() {
+                weights.push(draws.ln_colour(colour, total) + ln_prior * total as f64);
+            }
+            ln_totals.push(draws.ln_total(total));
+            sums[0].push(if n == 0 { 0.0 } else { f64::NEG_INFINITY });
+            for colour in 0..colours.len() {
+                let sum = ln_convolve_at(&sums[colour], &weights[colour], n);
+                sums[colour + 1].push(sum);
+            }
+            if total < least {
+                continue;
+            }
+            // Each colour's weight is concave in its count, so the most
+            // probable bag of a total is the one of the total before with a
+            // cube more of the colour that gains most from it.
+            let gain = |colour: usize| {
+                weights[colour][counts[colour] as usize + 1]
+                    - weights[colour][counts[colour] as usize]
+            };
+            if let (true, Some(colour)) = (
+                total > least,
+                (0..colours.len()).max_by(|a, b| gain(*a).total_cmp(&gain(*b))),
+            ) {
+                counts[colour] += 1;
+            }
+            let ln_posterior = counts
+                .iter()
+                .zip(&weights)
+                .map(|(count, weights)| weights[*count as usize])
+                .sum::<f64>()
+                - ln_totals[n];
+            // The smallest bag wins a tie.
+            if best
+                .as_ref()
+                .is_none_or(|(best, _)| ln_posterior > best + 1e-9)
+            {
+                best = Some((ln_posterior, counts.clone()));
+            }
+            let weight = sums[colours.len()][n] - ln_totals[n];
+            heaviest = heaviest.max(weight);
+            if weight < heaviest - TAIL {
+                bounded = false;
+                break;
+            }
+        }
+        let Some((_, best)) = best else {
+            return Ok(None);
+        };
+        let top = ln_totals.len();
+        // By total, ln of the summed weight of every bag of the colours from i
+        // on, so the bags of every colour but one are those of the colours
+        // before it alongside those after.
+        let mut rests = vec![sums[0].clone(); colours.len() + 1];
+        for colour in (1..colours.len()).rev() {
+            rests[colour] = (0..top)
+                .map(|n| ln_convolve_at(&rests[colour + 1], &weights[colour], n))
+                .collect();
+        }
+        let mut ranges = BTreeMap::new();
+        for (colour, name) in colours.iter().enumerate() {
+            control
+                .tick(colour, Some(colours.len()))
+                .ok_or(control.error())?;
+            let others: Vec<f64> = (0..top)
+                .map(|n| ln_convolve_at(&sums[colour], &rests[colour + 1], n))
+                .collect();
+            let mut marginal = Vec::with_capacity(top);
+            for count in 0..top {
+                let weight = match weights[colour][count] {
+                    f64::NEG_INFINITY => f64::NEG_INFINITY,
+                    weight => {
+                        weight
+                            + ln_sum(
+                                (count..top)
+                                    .filter(|n| others[n - count] > f64::NEG_INFINITY)
+                                    .map(|n| others[n - count] - ln_totals[n]),
+                            )
+                    }
+                };
+                marginal.push(weight);
+            }
+            ranges.insert((*name).clone(), credible(&marginal));
+        }
+        let total = best.iter().sum();
+        let log_likelihood = draws.log_likelihood(&best, total);
+        let bag = colours
+            .iter()
+            .zip(best)
+            .fold(Bag::new(), |bag, (colour, count)| bag.with(colour, count));
+        Ok(Some(Estimate {
+            bag,
+            log_likelihood,
+            ranges,
+            bounded,
+        }))
     }
 
     fn max_merge_round(&self) -> Round {
@@ -121,30 +531,307 @@ impl Game {
 }
 
 impl Games {
-    pub fn from_lines(lines: &Vec<String>) -> Option<Games> {
-        let mut games = HashMap::new();
+    pub fn from_lines(lines: &[String]) -> Option<Games> {
+        let mut games = Vec::new();
         for line in lines {
             let (pref_index_str, game_str) = line.split_once(": ")?;
             let index_str = pref_index_str.strip_prefix("Game ")?;
             let index = index_str.parse::<u32>().ok()?;
-            let game = Game::from_string(game_str)?;
-            games.insert(index, game);
+            games.push(Game::from_string(index, game_str)?);
         }
         Some(Games { games })
     }
 
-    pub fn sum_power_set(&self) -> u64 {
+    // The most probable bag behind each game by ID, under a prior of
+    // prior_mean cubes of each colour.
+    pub fn estimate(
+        &self,
+        prior_mean: f64,
+        max_cubes: u32,
+        control: &Control,
+    ) -> Result<Vec<(u32, Option<Estimate>)>, SolveError> {
         self.games
-            .values()
-            .map(|game| game.max_merge_round().power_set())
-            .sum()
+            .iter()
+            .map(|game| Ok((game.id, game.estimate(prior_mean, max_cubes, control)?)))
+            .collect()
     }
 
-    pub fn sum_possible(&self) -> u32 {
+    // Every game checked against the bag. None if a game shows a colour the
+    // bag rejects.
+    pub fn report(&self, bag: &Bag) -> Option<Vec<GameReport>> {
+        self.games.iter().map(|game| game.report(bag)).collect()
+    }
+
+    // ln of the chance of the game with the ID coming out as it did from the
+    // bag, negative infinity if it could not have. None for an unknown game
+    // or a bag of more cubes than can be counted.
+    pub fn log_likelihood(&self, id: u32, bag: &Bag) -> Option<f64> {
         self.games
             .iter()
-            .filter(|(_, b)| b.possible())
-            .map(|(a, _)| *a)
-            .sum()
+            .find(|game| game.id == id)?
+            .log_likelihood(bag)
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    pub fn sum_power_set(&self) -> Option<u64> {
+        checked_sum_opt(
+            self.games
+                .iter()
+                .map(|game| game.max_merge_round().power_set()),
+        )
+    }
+
+    // The IDs of the games the bag could have been used for. None if a game
+    // shows a colour the bag rejects.
+    pub fn possible_games(&self, bag: &Bag) -> Option<Vec<u32>> {
+        let mut possible = Vec::new();
+        for game in &self.games {
+            if game.possible(bag)? {
+                possible.push(game.id);
+            }
+        }
+        Some(possible)
+    }
+
+    // The sum of the IDs of the games the bag could have been used for. A
+    // colour the bag rejects fails like a parse error, as it did back when
+    // only red, green and blue were read.
+    pub fn sum_possible(&self, bag: &Bag) -> Result<u32, SolveError> {
+        let possible = self.possible_games(bag).ok_or(SolveError::Parse)?;
+        checked_sum(possible).ok_or(SolveError::Overflow)
+    }
+
+    // The smallest bag every game could have been played with: the most
+    // cubes of each colour shown in any one round.
+    pub fn minimal_bag(&self) -> Bag {
+        self.games
+            .iter()
+            .fold(Round::empty(), |a, game| {
+                a.max_merge(&game.max_merge_round())
+            })
+            .into_bag()
+    }
+
+    // For each colour, the count the minimal bag needs and the IDs of the
+    // games that need that many, so that any smaller bag rules them out.
+    pub fn binding_games(&self) -> BTreeMap<String, (u32, Vec<u32>)> {
+        let mut binding = BTreeMap::new();
+        for (colour, limit) in self.minimal_bag().limits {
+            let ids: Vec<u32> = self
+                .games
+                .iter()
+                .filter(|game| game.max_merge_round().count(&colour) == limit)
+                .map(|game| game.id)
+                .collect();
+            binding.insert(colour, (limit, ids));
+        }
+        binding
+    }
+}
+
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shown = self
+            .cubes
+            .iter()
+            .map(|(colour, count)| format!("{} {}", count, colour));
+        join(f, shown, ", ")
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        join(f, &self.rounds, "; ")
+    }
+}
+
+impl fmt::Display for Games {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let games = self
+            .games
+            .iter()
+            .map(|game| format!("Game {}: {}", game.id, game));
+        join(f, games, "\n")
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shown = self
+            .limits
+            .iter()
+            .map(|(colour, limit)| format!("{} {}", limit, colour));
+        join(f, shown, ", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn games(text: &str) -> Games {
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        Games::from_lines(&lines).unwrap()
+    }
+
+    #[test]
+    fn an_unknown_colour_follows_the_bag_policy() {
+        let games = games("Game 1: 3 red, 2 purple\nGame 2: 4 red; 1 purple");
+        let bag = Bag::new().with("red", 3);
+        assert_eq!(games.possible_games(&bag), None);
+        assert_eq!(games.sum_possible(&bag), Err(SolveError::Parse));
+        let ignoring = bag.clone().with_unknown(UnknownColour::Ignore);
+        assert_eq!(games.possible_games(&ignoring), Some(vec![1]));
+        let zero = bag.clone().with_unknown(UnknownColour::Zero);
+        assert_eq!(games.possible_games(&zero), Some(vec![]));
+        let purple = bag.with("purple", 2).with_unknown(UnknownColour::Zero);
+        assert_eq!(games.sum_possible(&purple), Ok(1));
+    }
+
+    // Game 1 needs the most red and the most blue, so binds both.
+    #[test]
+    fn one_game_can_bind_two_colours() {
+        let games =
+            games("Game 1: 5 red, 4 blue\nGame 2: 5 red; 2 blue, 1 green\nGame 3: 1 red, 1 green");
+        assert!(games.minimal_bag() == Bag::new().with("red", 5).with("blue", 4).with("green", 1));
+        let binding: Vec<(String, (u32, Vec<u32>))> = games.binding_games().into_iter().collect();
+        assert_eq!(
+            binding,
+            [
+                ("blue".to_string(), (4, vec![1])),
+                ("green".to_string(), (1, vec![2, 3])),
+                ("red".to_string(), (5, vec![1, 2])),
+            ]
+        );
+        assert_eq!(
+            games.possible_games(&Bag::new().with("red", 5).with("blue", 3).with("green", 1)),
+            Some(vec![2, 3])
+        );
+    }
+
+    #[test]
+    fn reports_games_in_input_order() {
+        let games = games(
+            "Game 3: 1 red; 13 red, 15 blue; 20 green\nGame 1: 1 blue\nGame 2: 2 red, 3 green, 4 blue; 1 red",
+        );
+        let reports = games.report(&Bag::puzzle()).unwrap();
+        let ids: Vec<u32> = reports.iter().map(|report| report.id).collect();
+        assert_eq!(ids, [3, 1, 2]);
+        assert_eq!(reports[0].rounds, 3);
+        assert_eq!(reports[0].power, Some(13 * 20 * 15));
+        // Round 2 breaks two limits, and blue comes first.
+        let violation = reports[0].violation.as_ref().unwrap();
+        assert_eq!(violation.round, 2);
+        assert_eq!(violation.colour, "blue");
+        assert_eq!((violation.count, violation.limit), (15, 14));
+        assert!(reports[1].violation.is_none());
+        assert_eq!(reports[1].power, Some(0));
+        assert!(reports[2].minimal == Bag::new().with("red", 2).with("green", 3).with("blue", 4));
+        assert_eq!(reports[2].power, Some(24));
+        assert!(games.report(&Bag::new().with("red", 12)).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_bag_keeps_its_policy_through_json() {
+        let bag = Bag::puzzle().with_unknown(UnknownColour::Zero);
+        let json = serde_json::to_string(&bag).unwrap();
+        assert!(serde_json::from_str::<Bag>(&json).unwrap() == bag);
+        let old: Bag = serde_json::from_str(r#"{"limits": {"red": 12}}"#).unwrap();
+        assert!(old == Bag::new().with("red", 12));
+    }
+
+    #[test]
+    fn convolves_in_ln() {
+        let a = [1f64.ln(), 2f64.ln()];
+        let b = [3f64.ln(), 4f64.ln()];
+        assert!(close(ln_convolve_at(&a, &b, 0), 3f64.ln()));
+        assert!(close(ln_convolve_at(&a, &b, 1), 10f64.ln()));
+    }
+
+    #[test]
+    fn credible_range_leaves_out_both_tails() {
+        let ln = |weights: &[f64]| weights.iter().map(|w| w.ln()).collect::<Vec<f64>>();
+        assert_eq!(credible(&ln(&[1.0, 98.0, 1.0])), (1, 1));
+        assert_eq!(credible(&ln(&[10.0, 80.0, 10.0])), (0, 2));
+        assert_eq!(credible(&ln(&[0.0, 0.0, 1.0, 3.0])), (2, 3));
+    }
+
+    // One red pair drawn under a prior halving with each cube: every bag of
+    // two or more red is equally likely to draw it, so the posterior of k
+    // cubes is 2^(1-k), whose first 5% and last 95% come at 2 and 6.
+    #[test]
+    fn estimates_a_single_round_of_one_colour() {
+        let game = Game::from_string(1, "2 red").unwrap();
+        let estimate = game.estimate(1.0, 1000, &Control::new()).unwrap().unwrap();
+        assert!(estimate.bag == Bag::new().with("red", 2));
+        assert!(close(estimate.log_likelihood, 0.0));
+        assert_eq!(estimate.ranges["red"], (2, 6));
+        assert!(!estimate.bounded);
+    }
+
+    #[test]
+    fn a_bag_past_the_maximum_is_no_estimate() {
+        let game = Game::from_string(1, "3 red, 2 blue").unwrap();
+        assert!(game.estimate(1.0, 4, &Control::new()).unwrap().is_none());
+        let estimate = game.estimate(1.0, 5, &Control::new()).unwrap().unwrap();
+        assert!(estimate.bag == Bag::new().with("red", 3).with("blue", 2));
+        assert!(estimate.bounded);
+    }
+
+    // Every bag of two colours weighed out in full.
+    #[test]
+    fn matches_every_bag_weighed_out() {
+        let game = Game::from_string(1, "3 red, 1 blue; 1 red, 2 blue").unwrap();
+        let prior_mean = 2.0;
+        let estimate = game
+            .estimate(prior_mean, 1000, &Control::new())
+            .unwrap()
+            .unwrap();
+        let ln_prior = (prior_mean / (prior_mean + 1.0)).ln();
+        let draws = [(3, 1), (1, 2)];
+        let top = 80;
+        let mut best = (f64::NEG_INFINITY, (0, 0));
+        let (mut red, mut blue) = (vec![Vec::new(); top], vec![Vec::new(); top]);
+        for r in 0..top as u32 {
+            for b in 0..top as u32 - r {
+                if draws.iter().any(|(dr, db)| r < *dr || b < *db) {
+                    continue;
+                }
+                let weight = draws
+                    .iter()
+                    .map(|(dr, db)| {
+                        ln_choose(r, *dr) + ln_choose(b, *db) - ln_choose(r + b, dr + db)
+                    })
+                    .sum::<f64>()
+                    + ln_prior * (r + b) as f64;
+                if weight > best.0 + 1e-9 {
+                    best = (weight, (r, b));
+                }
+                red[r as usize].push(weight);
+                blue[b as usize].push(weight);
+            }
+        }
+        let marginal = |weights: Vec<Vec<f64>>| -> Vec<f64> {
+            weights
+                .into_iter()
+                .map(|weights| ln_sum(weights.into_iter()))
+                .collect()
+        };
+        let (r, b) = best.1;
+        assert!(estimate.bag == Bag::new().with("red", r).with("blue", b));
+        assert_eq!(estimate.ranges["red"], credible(&marginal(red)));
+        assert_eq!(estimate.ranges["blue"], credible(&marginal(blue)));
     }
 }
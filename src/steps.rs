@@ -1,5 +1,9 @@
+use crate::utils::render;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Map {
     rocks: HashSet<(i64, i64)>,
     height: i64,
@@ -8,7 +12,7 @@ pub struct Map {
 }
 
 impl Map {
-    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Map {
+    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Option<Map> {
         let rocks = grid
             .iter()
             .filter(|(_, ch)| **ch == '#')
@@ -18,16 +22,15 @@ impl Map {
             .iter()
             .filter(|(_, ch)| **ch == 'S')
             .map(|(id, _)| *id)
-            .next()
-            .unwrap();
+            .next()?;
         let height = grid.iter().map(|((i, _), _)| i + 1).max().unwrap_or(0);
         let width = grid.iter().map(|((_, j), _)| j + 1).max().unwrap_or(0);
-        Map {
+        Some(Map {
             rocks,
             start,
             height,
             width,
-        }
+        })
     }
 
     pub fn steps(&self, num: usize) -> usize {
@@ -68,3 +71,18 @@ impl Map {
         !self.rocks.contains(&(i, j))
     }
 }
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = render(self.height, self.width, |i, j| {
+            if (i, j) == self.start {
+                'S'
+            } else if self.rocks.contains(&(i, j)) {
+                '#'
+            } else {
+                '.'
+            }
+        });
+        write!(f, "{}", grid)
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::control::Control;
+use super::{challenge, history, io, render};
+
+// How long a solver may run before the dashboard gives up on it.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// How often the dashboard looks for finished work while waiting for a key.
+const POLL: Duration = Duration::from_millis(100);
+
+// The thread solvers and frames run on, whose panics are kept off the screen.
+const WORKER: &str = "tui-worker";
+
+struct Run {
+    outcome: Result<String, String>,
+    time_ms: f64,
+}
+
+struct Frames {
+    day: u8,
+    pages: Vec<String>,
+    page: usize,
+    scroll: u16,
+}
+
+// What a job on the worker thread comes back with.
+enum Finished {
+    Solved(u8, Run),
+    Frames(Frames),
+    Failed(String),
+}
+
+// Work running off the UI thread, so the dashboard keeps drawing meanwhile.
+struct Job {
+    label: String,
+    // Set to stop a solver at its next tick. Rendering frames cannot stop.
+    cancel: Option<Arc<AtomicBool>>,
+    done: Receiver<Finished>,
+}
+
+struct App {
+    // The latest run of each challenge, seeded from the history.
+    runs: HashMap<u8, Run>,
+    days: ListState,
+    frames: Option<Frames>,
+    status: Option<String>,
+    job: Option<Job>,
+}
+
+// Puts the terminal back however the dashboard ends, a panic included.
+struct Restore;
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        // Drops the hook that hides worker panics. Hooks cannot be swapped
+        // while panicking, and the process is going down then anyway.
+        if !thread::panicking() {
+            let _ = panic::take_hook();
+        }
+        ratatui::restore();
+    }
+}
+
+// Runs the work on the worker thread. A panic comes back as a failure.
+fn spawn(
+    label: String,
+    cancel: Option<Arc<AtomicBool>>,
+    work: impl FnOnce() -> Finished + Send + 'static,
+) -> std::io::Result<Job> {
+    let (sender, done) = mpsc::channel();
+    let panicked = format!("{} panicked", label);
+    thread::Builder::new()
+        .name(WORKER.to_string())
+        .spawn(move || {
+            let finished =
+                panic::catch_unwind(AssertUnwindSafe(work)).unwrap_or(Finished::Failed(panicked));
+            let _ = sender.send(finished);
+        })?;
+    Ok(Job {
+        label,
+        cancel,
+        done,
+    })
+}
+
+fn solve(day: u8, part: u8, num: u8, control: Control) -> Finished {
+    let input = match io::read_input(day as i8, false) {
+        Ok(input) => input,
+        Err(err) => {
+            let outcome = Err(err.to_string());
+            return Finished::Solved(
+                num,
+                Run {
+                    outcome,
+                    time_ms: 0.0,
+                },
+            );
+        }
+    };
+    let start = Instant::now();
+    let result = challenge::solve(day, part, &input, &control);
+    let elapsed = start.elapsed();
+    let implementation = challenge::implementations(num)[0];
+    let record = history::Record::new(num, implementation, &input, &result, elapsed);
+    // The dashboard still works without a history file.
+    let _ = history::append(&record);
+    let outcome = result
+        .map(|answer| answer.to_string())
+        .map_err(|err| err.to_string());
+    let time_ms = elapsed.as_secs_f64() * 1000.0;
+    Finished::Solved(num, Run { outcome, time_ms })
+}
+
+fn frames(day: u8) -> Finished {
+    let pages = io::read_input(day as i8, false)
+        .map_err(|err| err.to_string())
+        .and_then(|input| render::frames(day, &input).map_err(|err| err.to_string()));
+    match pages {
+        Ok(pages) => Finished::Frames(Frames {
+            day,
+            pages,
+            page: 0,
+            scroll: 0,
+        }),
+        Err(err) => Finished::Failed(err),
+    }
+}
+
+impl App {
+    fn new() -> App {
+        let mut runs = HashMap::new();
+        for record in history::load() {
+            let outcome = match (record.answer, record.error) {
+                (Some(answer), _) => Ok(answer),
+                (None, error) => Err(error.unwrap_or_default()),
+            };
+            let time_ms = record.time_ms;
+            runs.insert(record.challenge, Run { outcome, time_ms });
+        }
+        App {
+            runs,
+            days: ListState::default().with_selected(Some(0)),
+            frames: None,
+            status: None,
+            job: None,
+        }
+    }
+
+    fn day(&self) -> u8 {
+        self.days.selected().unwrap_or(0) as u8 + 1
+    }
+
+    fn stars(&self, day: u8) -> String {
+        (1..=2)
+            .map(|part| {
+                let solved = challenge::number(day, part)
+                    .and_then(|num| self.runs.get(&num))
+                    .is_some_and(|run| run.outcome.is_ok());
+                if solved {
+                    '*'
+                } else {
+                    ' '
+                }
+            })
+            .collect()
+    }
+
+    fn solve(&mut self, day: u8, part: u8) {
+        let Some(num) = challenge::number(day, part) else {
+            return;
+        };
+        let control = Control::new().with_timeout(TIMEOUT);
+        let cancel = control.cancel_flag();
+        let label = format!("Day {} part {}", day, part);
+        self.start(spawn(label, Some(cancel), move || {
+            solve(day, part, num, control)
+        }));
+    }
+
+    fn open_frames(&mut self) {
+        let day = self.day();
+        let label = format!("Frames for day {}", day);
+        self.start(spawn(label, None, move || frames(day)));
+    }
+
+    fn start(&mut self, job: std::io::Result<Job>) {
+        match job {
+            Ok(job) => self.job = Some(job),
+            Err(err) => self.status = Some(err.to_string()),
+        }
+    }
+
+    fn finish(&mut self) {
+        let Some(job) = &self.job else {
+            return;
+        };
+        let finished = match job.done.try_recv() {
+            Ok(finished) => finished,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Finished::Failed(format!("{} stopped", job.label)),
+        };
+        self.job = None;
+        match finished {
+            Finished::Solved(num, run) => {
+                self.runs.insert(num, run);
+            }
+            Finished::Frames(frames) => self.frames = Some(frames),
+            Finished::Failed(err) => self.status = Some(err),
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            self.finish();
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(POLL)? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            self.status = None;
+            if let Some(job) = &self.job {
+                match key.code {
+                    KeyCode::Char('q') => return Ok(()),
+                    KeyCode::Esc => {
+                        if let Some(cancel) = &job.cancel {
+                            cancel.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            if let Some(frames) = &mut self.frames {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') => self.frames = None,
+                    KeyCode::Right | KeyCode::Char('l') => {
+                        frames.page = (frames.page + 1).min(frames.pages.len() - 1)
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => {
+                        frames.page = frames.page.saturating_sub(1)
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => frames.scroll += 1,
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        frames.scroll = frames.scroll.saturating_sub(1)
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.days.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.days.select_previous(),
+                KeyCode::Char(ch @ ('1' | '2')) => self.solve(self.day(), ch as u8 - b'0'),
+                KeyCode::Char('f') | KeyCode::Enter => self.open_frames(),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        if let Some(frames) = &self.frames {
+            let title = format!(
+                " Day {} frame {}/{}  ←/→ page  ↑/↓ scroll  esc back ",
+                frames.day,
+                frames.page + 1,
+                frames.pages.len()
+            );
+            let page = Paragraph::new(frames.pages[frames.page].as_str())
+                .scroll((frames.scroll, 0))
+                .block(Block::bordered().title(title));
+            frame.render_widget(page, frame.area());
+            return;
+        }
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Length(16), Constraint::Min(0)]).areas(frame.area());
+        let items: Vec<ListItem> = (1..=25)
+            .map(|day| ListItem::new(format!("Day {:>2}  {}", day, self.stars(day))))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Days "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.days);
+
+        let day = self.day();
+        let mut lines = Vec::new();
+        for part in 1..=2 {
+            let run = challenge::number(day, part).and_then(|num| self.runs.get(&num));
+            let line = match run {
+                Some(Run {
+                    outcome: Ok(answer),
+                    time_ms,
+                }) => Line::from(format!("Part {}: {}  ({:.1}ms)", part, answer, time_ms)),
+                Some(Run {
+                    outcome: Err(err), ..
+                }) => Line::from(vec![
+                    Span::raw(format!("Part {}: ", part)),
+                    Span::styled(err.clone(), Style::new().fg(Color::Red)),
+                ]),
+                None => Line::from(format!("Part {}: not run", part)),
+            };
+            lines.push(line);
+        }
+        lines.push(Line::from(""));
+        if let Some(job) = &self.job {
+            let stop = if job.cancel.is_some() {
+                "  esc cancel"
+            } else {
+                ""
+            };
+            lines.push(Line::from(format!("{}...{}", job.label, stop)));
+            lines.push(Line::from(""));
+        }
+        if let Some(status) = &self.status {
+            lines.push(Line::from(status.as_str()));
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from("1/2 run a part  f frames  ↑/↓ select  q quit"));
+        let detail = Paragraph::new(lines).block(Block::bordered().title(format!(" Day {} ", day)));
+        frame.render_widget(detail, detail_area);
+    }
+}
+
+pub fn dashboard() -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let _restore = Restore;
+    // Worker panics come back as failures, keep their messages from
+    // scribbling over the screen. Any other panic still restores the
+    // terminal through the hook init installed.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() != Some(WORKER) {
+            hook(info);
+        }
+    }));
+    App::new().run(&mut terminal)
+}
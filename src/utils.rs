@@ -1,4 +1,7 @@
+use std::fmt;
+
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir {
     Up,
     Down,
@@ -38,6 +41,16 @@ impl Dir {
         }
     }
 
+    // The arrow pointing this way, for drawing a grid.
+    pub fn to_char(&self) -> char {
+        match self {
+            Dir::Up => '^',
+            Dir::Down => 'v',
+            Dir::Left => '<',
+            Dir::Right => '>',
+        }
+    }
+
     pub fn turn(&self) -> [Dir; 2] {
         match self {
             Dir::Up => [Dir::Left, Dir::Right],
@@ -47,3 +60,27 @@ impl Dir {
         }
     }
 }
+
+// Draws a height by width grid a row at a time.
+pub fn render(height: i64, width: i64, cell: impl Fn(i64, i64) -> char) -> String {
+    (0..height)
+        .map(|i| (0..width).map(|j| cell(i, j)).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Writes each item with sep between them, for models written a line or a
+// field at a time.
+pub fn join<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    items: impl IntoIterator<Item = T>,
+    sep: &str,
+) -> fmt::Result {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", sep)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
@@ -1,7 +1,13 @@
-use petgraph::algo::all_simple_paths;
+use crate::answer::SolveError;
+use crate::control::Control;
+use crate::utils::render;
 use petgraph::graphmap::DiGraphMap;
-use std::collections::HashMap;
+use std::cmp::max;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Tile {
     Empty,
     N,
@@ -10,7 +16,10 @@ enum Tile {
     W,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Woods {
+    #[cfg_attr(feature = "serde", serde(with = "crate::grid_serde"))]
     tiles: HashMap<(i64, i64), Tile>,
 }
 
@@ -26,6 +35,16 @@ impl Tile {
         }
     }
 
+    fn to_char(&self) -> char {
+        match self {
+            Tile::E => '>',
+            Tile::N => '^',
+            Tile::W => '<',
+            Tile::S => 'v',
+            Tile::Empty => '.',
+        }
+    }
+
     fn next(&self, i: i64, j: i64, uphill: bool) -> Vec<(i64, i64)> {
         if uphill {
             return vec![(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)];
@@ -41,23 +60,56 @@ impl Tile {
 }
 
 impl Woods {
-    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Woods {
-        let tiles = grid
+    pub fn from_grid(grid: &HashMap<(i64, i64), char>) -> Option<Woods> {
+        let tiles: HashMap<(i64, i64), Tile> = grid
             .iter()
             .filter_map(|(ind, ch)| Tile::from_char(ch).map(|t| (*ind, t)))
             .collect();
-        Woods { tiles }
+        if tiles.is_empty() {
+            return None;
+        }
+        Some(Woods { tiles })
     }
 
-    pub fn maximum_path(&self, uphill: bool) -> usize {
+    // Fails if cancelled before every path was explored, or if no path leads
+    // from the start to the end.
+    pub fn maximum_path(&self, uphill: bool, control: &Control) -> Result<usize, SolveError> {
         let graph = self.graph(uphill);
         let start = self.start();
         let end = self.end();
-        all_simple_paths::<Vec<_>, _>(&graph, start, end, 0, None)
-            .map(|i| i.len())
-            .max()
-            .unwrap()
-            - 1
+        let mut longest = None;
+        let mut paths = 0;
+        // The tiles of the path so far, each with the neighbours still to
+        // try from it.
+        let mut on_path = HashSet::from([start]);
+        let mut stack = vec![(start, graph.neighbors(start))];
+        // A step tries a single neighbour, so counting them keeps the checks
+        // close together however long the search goes without reaching the end.
+        for step in 1usize.. {
+            if step % 1024 == 0 {
+                control.tick(paths, None).ok_or(control.error())?;
+            }
+            let Some((_, next)) = stack.last_mut() else {
+                break;
+            };
+            match next.next() {
+                Some(tile) if tile == end => {
+                    paths += 1;
+                    longest = max(longest, Some(stack.len()));
+                }
+                Some(tile) => {
+                    if on_path.insert(tile) {
+                        stack.push((tile, graph.neighbors(tile)));
+                    }
+                }
+                None => {
+                    if let Some((tile, _)) = stack.pop() {
+                        on_path.remove(&tile);
+                    }
+                }
+            }
+        }
+        longest.ok_or(SolveError::NoAnswer)
     }
 
     fn start(&self) -> (i64, i64) {
@@ -80,3 +132,35 @@ impl Woods {
         graph
     }
 }
+
+// Only the paths are kept, so the forest fills in everything else, including
+// the wall down the right hand side.
+impl fmt::Display for Woods {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let height = self.tiles.keys().map(|(i, _)| i + 1).max().unwrap_or(0);
+        let width = self.tiles.keys().map(|(_, j)| j + 2).max().unwrap_or(0);
+        let grid = render(height, width, |i, j| {
+            self.tiles.get(&(i, j)).map_or('#', |tile| tile.to_char())
+        });
+        write!(f, "{}", grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io;
+
+    #[test]
+    fn no_path_to_the_end_has_no_answer() {
+        let woods = Woods::from_grid(&io::grid("#.#\n###\n#.#")).unwrap();
+        let longest = woods.maximum_path(false, &Control::new());
+        assert_eq!(longest, Err(SolveError::NoAnswer));
+    }
+
+    #[test]
+    fn walks_the_only_path() {
+        let woods = Woods::from_grid(&io::grid("#.#\n#.#\n#.#")).unwrap();
+        assert_eq!(woods.maximum_path(false, &Control::new()), Ok(2));
+    }
+}
@@ -1,5 +1,10 @@
-use std::collections::HashMap;
+use crate::answer::{checked_product, checked_sum, checked_sum_opt};
+use crate::utils::join;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Property {
     X,
     M,
@@ -7,17 +12,22 @@ enum Property {
     S,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Op {
     LT,
     GT,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Destination {
     Accept,
     Reject,
     Workflow(String),
 }
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Part {
     x: i64,
     m: i64,
@@ -25,6 +35,8 @@ struct Part {
     s: i64,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Rule {
     prop: Property,
     op: Op,
@@ -32,11 +44,15 @@ struct Rule {
     res: Destination,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Workflow {
     rules: Vec<Rule>,
     default: Destination,
 }
 
+#[derive(PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pile {
     parts: Vec<Part>,
     workflows: HashMap<String, Workflow>,
@@ -47,35 +63,71 @@ impl Pile {
         let (workflow_str, part_str) = string.split_once("\n\n")?;
         let workflows = workflow_str
             .lines()
-            .map(|line| Workflow::from_line(&line))
+            .map(Workflow::from_line)
             .collect::<Option<HashMap<String, Workflow>>>()?;
         let parts = part_str
             .lines()
-            .map(|line| Part::from_line(&line))
+            .map(Part::from_line)
             .collect::<Option<Vec<Part>>>()?;
-        Some(Pile { workflows, parts })
+        let pile = Pile { workflows, parts };
+        if !pile.workflows.contains_key("in") || !pile.is_acyclic() {
+            return None;
+        }
+        Some(pile)
     }
 
-    pub fn accepted_ratings(&self) -> i64 {
-        self.parts
-            .iter()
-            .filter(|part| self.accept(part))
-            .map(|part| part.rating())
-            .sum()
+    // Every workflow referenced has to exist and none may lead back to itself,
+    // otherwise a part would never be accepted or rejected.
+    fn is_acyclic(&self) -> bool {
+        let mut done: HashSet<&str> = HashSet::new();
+        self.workflows
+            .keys()
+            .all(|name| self.visit(name, &mut HashSet::new(), &mut done))
     }
 
-    pub fn accepted_combos(&self) -> i64 {
-        self.accepted_ranges()
-            .iter()
-            .map(|range| range.size())
-            .sum()
+    fn visit<'a>(
+        &'a self,
+        name: &'a str,
+        path: &mut HashSet<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> bool {
+        if done.contains(name) {
+            return true;
+        }
+        let Some(workflow) = self.workflows.get(name) else {
+            return false;
+        };
+        if !path.insert(name) {
+            return false;
+        }
+        let valid = workflow
+            .destinations()
+            .all(|destination| match destination {
+                Destination::Workflow(next) => self.visit(next, path, done),
+                _ => true,
+            });
+        path.remove(name);
+        done.insert(name);
+        valid
+    }
+
+    pub fn accepted_ratings(&self) -> Option<i64> {
+        checked_sum_opt(
+            self.parts
+                .iter()
+                .filter(|part| self.accept(part))
+                .map(|part| part.rating()),
+        )
+    }
+
+    pub fn accepted_combos(&self) -> Option<i64> {
+        checked_sum_opt(self.accepted_ranges().iter().map(|range| range.size()))
     }
 
     fn accepted_ranges(&self) -> Vec<PartRange> {
         let mut to_check: Vec<(String, PartRange)> = vec![("in".to_string(), PartRange::new())];
         let mut ranges: Vec<PartRange> = Vec::new();
-        while !to_check.is_empty() {
-            let (workflow_name, range) = to_check.pop().unwrap();
+        while let Some((workflow_name, range)) = to_check.pop() {
             let workflow = self.workflows.get(&workflow_name).unwrap();
             for (destination, processed_range) in workflow.process(&range) {
                 match destination {
@@ -125,8 +177,8 @@ impl Part {
         Some(part)
     }
 
-    fn rating(&self) -> i64 {
-        self.x + self.m + self.a + self.s
+    fn rating(&self) -> Option<i64> {
+        checked_sum([self.x, self.m, self.a, self.s])
     }
 
     fn value(&self, prop: &Property) -> i64 {
@@ -148,11 +200,18 @@ impl Workflow {
         let default = Destination::from_string(default_str);
         let rules = rule_strings
             .iter()
-            .map(|string| Rule::from_string(*string))
+            .map(|string| Rule::from_string(string))
             .collect::<Option<Vec<Rule>>>()?;
         Some((name.to_string(), Workflow { rules, default }))
     }
 
+    fn destinations(&self) -> impl Iterator<Item = &Destination> {
+        self.rules
+            .iter()
+            .map(|rule| &rule.res)
+            .chain(std::iter::once(&self.default))
+    }
+
     fn send(&self, part: &Part) -> Destination {
         self.rules
             .iter()
@@ -292,10 +351,61 @@ impl PartRange {
         }
     }
 
-    fn size(&self) -> i64 {
-        (self.x.1 - self.x.0)
-            * (self.m.1 - self.m.0)
-            * (self.a.1 - self.a.0)
-            * (self.s.1 - self.s.0)
+    fn size(&self) -> Option<i64> {
+        checked_product([self.x, self.m, self.a, self.s].map(|(min, max)| max - min))
+    }
+}
+
+impl fmt::Display for Property {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Property::X => write!(f, "x"),
+            Property::M => write!(f, "m"),
+            Property::A => write!(f, "a"),
+            Property::S => write!(f, "s"),
+        }
+    }
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Destination::Accept => write!(f, "A"),
+            Destination::Reject => write!(f, "R"),
+            Destination::Workflow(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Op::LT => '<',
+            Op::GT => '>',
+        };
+        write!(f, "{}{}{}:{}", self.prop, op, self.val, self.res)
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{x={},m={},a={},s={}}}", self.x, self.m, self.a, self.s)
+    }
+}
+
+impl fmt::Display for Pile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names: Vec<&String> = self.workflows.keys().collect();
+        names.sort();
+        for name in names {
+            let workflow = &self.workflows[name];
+            write!(f, "{}{{", name)?;
+            for rule in &workflow.rules {
+                write!(f, "{},", rule)?;
+            }
+            writeln!(f, "{}}}", workflow.default)?;
+        }
+        writeln!(f)?;
+        join(f, &self.parts, "\n")
     }
 }
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use super::answer::SolveError;
+use super::*;
+
+// The text a model writes, and whether parsing that text gives the same
+// model back.
+fn written<T: PartialEq + fmt::Display>(
+    input: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<(String, bool), SolveError> {
+    let model = parse(input).ok_or(SolveError::Parse)?;
+    let text = model.to_string();
+    let same = parse(&text).is_some_and(|again| again == model);
+    Ok((text, same))
+}
+
+fn write_checked(day: u8, input: &str) -> Result<(String, bool), SolveError> {
+    let input = io::normalize(input);
+    match day {
+        1 => written(&input, |text| Some(text.to_string())),
+        2 => written(&input, |text| {
+            snow_game::Games::from_lines(&io::lines(text))
+        }),
+        3 => written(&input, |text| parts::Engine::from_lines(&io::lines(text))),
+        4 => written(&input, |text| {
+            scratchcards::ScratchCards::from_lines(&io::lines(text))
+        }),
+        5 => written(&input, seeds::Almanac::from_string),
+        6 => written(&input, |text| race::Races::from_lines(&io::lines(text))),
+        7 => written(&input, |text| {
+            cards::Game::from_lines(&io::lines(text), false)
+        }),
+        8 => written(&input, map::Map::from_string),
+        9 => written(&input, |text| {
+            sequences::SequenceGens::from_lines(&io::lines(text))
+        }),
+        10 => written(&input, |text| pipes::Pipes::from_grid(&io::grid(text))),
+        // An expansion of one leaves the image as it is.
+        11 => written(&input, |text| galaxy::Galaxy::from_grid(1, &io::grid(text))),
+        13 => written(&input, |text| {
+            Some(cave::Caves::from_grids(&io::grids(text)))
+        }),
+        14 => written(&input, |text| Some(beams::Beam::from_grid(&io::grid(text)))),
+        15 => written(&input, hash::Commands::from_line),
+        16 => written(&input, |text| {
+            Some(mirrors::MirrorCave::from_grid(&io::grid(text)))
+        }),
+        17 => written(&input, |text| crucible::City::from_grid(&io::grid(text))),
+        18 => written(&input, |text| lake::Path::from_lines(&io::lines(text))),
+        19 => written(&input, workflow::Pile::from_string),
+        20 => written(&input, |text| {
+            signals::Network::from_lines(&io::lines(text))
+        }),
+        21 => written(&input, |text| steps::Map::from_grid(&io::grid(text))),
+        22 => written(&input, |text| bricks::Bricks::from_lines(&io::lines(text))),
+        23 => written(&input, |text| walk::Woods::from_grid(&io::grid(text))),
+        24 => written(&input, |text| hail::Storm::from_lines(&io::lines(text))),
+        _ => Err(SolveError::NoSolver),
+    }
+}
+
+// The puzzle text a day's parsed model writes back out. Anything the model
+// does not keep, such as the order of unordered sections, comes out in one
+// canonical form.
+pub fn write(day: u8, input: &str) -> Result<String, SolveError> {
+    Ok(write_checked(day, input)?.0)
+}
+
+// Whether the written text parses back to the same model, otherwise the
+// writer has lost something the parser reads.
+pub fn round_trips(day: u8, input: &str) -> Result<bool, SolveError> {
+    Ok(write_checked(day, input)?.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAYS: [u8; 23] = [
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+    ];
+
+    #[test]
+    fn every_input_round_trips() {
+        for day in DAYS {
+            let input = io::read_input(day as i8, false).unwrap();
+            assert_eq!(round_trips(day, &input), Ok(true), "day {}", day);
+        }
+    }
+
+    #[test]
+    fn engine_keeps_numbers_next_to_no_symbol() {
+        let input = "467..114..\n...*......\n..35..633.";
+        assert_eq!(round_trips(3, input), Ok(true));
+        assert_eq!(write(3, input).unwrap(), input);
+    }
+
+    #[test]
+    fn lake_keeps_colours() {
+        let input = "R 6 (#70c710)\nD 5 (#0dc571)\nL 6 (#5713f0)\nU 5 (#d2c081)";
+        assert_eq!(round_trips(18, input), Ok(true));
+        assert_eq!(write(18, input).unwrap(), input);
+    }
+
+    #[test]
+    fn unparsed_input_is_an_error() {
+        assert_eq!(round_trips(18, "R x"), Err(SolveError::Parse));
+        assert_eq!(round_trips(12, ""), Err(SolveError::NoSolver));
+    }
+}